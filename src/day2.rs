@@ -1,74 +1,432 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 
-#[derive(Debug)]
-pub struct Round((char, char));
+/// The result of a round from the player's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// The shape a player can show, along with its score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    pub name: String,
+    pub score: u32,
+}
+
+/// The rule set of the game: shapes, which shape beats which,
+/// scores for shapes and outcomes, and the letters used in the guide
+/// ```
+/// use aoc2022::{Outcome, Rules};
+///
+/// let rules = Rules::new(&[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+///     &[("Rock", "Scissors"), ("Scissors", "Paper"), ("Paper", "Rock")])
+///     .unwrap()
+///     .with_encoding("ABC", "XYZ", "XYZ")
+///     .unwrap();
+///
+/// let paper = rules.shape_by_name("Paper").unwrap();
+/// let rock = rules.shape_by_name("Rock").unwrap();
+/// assert_eq!(rules.outcome(paper, rock), Outcome::Win);
+/// assert_eq!(rules.score(paper, rock), 8);
+///
+/// let rules = Rules::rpsls();
+/// let spock = rules.shape_by_name("Spock").unwrap();
+/// let lizard = rules.shape_by_name("Lizard").unwrap();
+/// assert_eq!(rules.outcome(spock, lizard), Outcome::Loss);
+///
+/// // Cycles must be complete: every two shapes need a winner
+/// assert!(Rules::new(&[("Rock", 1), ("Paper", 2)], &[]).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rules {
+    shapes: Vec<Shape>,
+    beats: HashSet<(usize, usize)>,
+    outcome_scores: HashMap<Outcome, u32>,
+    opponent_codes: HashMap<char, usize>,
+    player_codes: HashMap<char, usize>,
+    outcome_codes: HashMap<char, Outcome>,
+}
+impl Rules {
+    // Build the rules from the list of (name, score) shapes
+    // and the list of (winner, loser) pairs
+    pub fn new(shapes: &[(&str, u32)], beats: &[(&str, &str)]) -> Result<Self, String> {
+        let mut names: HashMap<&str, usize> = HashMap::with_capacity(shapes.len());
+        for (index, (name, _)) in shapes.iter().enumerate() {
+            if names.insert(name, index).is_some() {
+                return Err(format!("Duplicated shape: {:?}", name));
+            }
+        }
+
+        let mut pairs = HashSet::with_capacity(beats.len());
+        for (winner, loser) in beats {
+            let w = *names
+                .get(winner)
+                .ok_or(format!("Unknown shape: {:?}", winner))?;
+            let l = *names
+                .get(loser)
+                .ok_or(format!("Unknown shape: {:?}", loser))?;
+            if w == l {
+                return Err(format!("Shape {:?} cannot beat itself", winner));
+            }
+            pairs.insert((w, l));
+        }
+
+        for a in 0..shapes.len() {
+            for b in (a + 1)..shapes.len() {
+                match (pairs.contains(&(a, b)), pairs.contains(&(b, a))) {
+                    (true, true) => {
+                        return Err(format!(
+                            "Shapes {:?} and {:?} beat each other",
+                            shapes[a].0, shapes[b].0
+                        ))
+                    }
+                    (false, false) => {
+                        return Err(format!(
+                            "No winner between {:?} and {:?}",
+                            shapes[a].0, shapes[b].0
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            shapes: shapes
+                .iter()
+                .map(|(name, score)| Shape {
+                    name: name.to_string(),
+                    score: *score,
+                })
+                .collect(),
+            beats: pairs,
+            outcome_scores: HashMap::from([
+                (Outcome::Loss, 0),
+                (Outcome::Draw, 3),
+                (Outcome::Win, 6),
+            ]),
+            opponent_codes: HashMap::new(),
+            player_codes: HashMap::new(),
+            outcome_codes: HashMap::new(),
+        })
+    }
+
+    // Replace the default scores for outcomes (0, 3, 6)
+    pub fn with_outcome_scores(mut self, loss: u32, draw: u32, win: u32) -> Self {
+        self.outcome_scores = HashMap::from([
+            (Outcome::Loss, loss),
+            (Outcome::Draw, draw),
+            (Outcome::Win, win),
+        ]);
+        self
+    }
+
+    // Set letters for the opponent's and player's shapes (in the order of shapes),
+    // and for the expected outcomes (in the order: loss, draw, win)
+    pub fn with_encoding(
+        mut self,
+        opponent: &str,
+        player: &str,
+        outcomes: &str,
+    ) -> Result<Self, String> {
+        self.opponent_codes = self.shape_codes(opponent)?;
+        self.player_codes = self.shape_codes(player)?;
+
+        let letters: Vec<char> = outcomes.chars().collect();
+        if letters.len() != 3 {
+            return Err(format!("Expected 3 outcome letters, got {:?}", outcomes));
+        }
+        self.outcome_codes = HashMap::with_capacity(3);
+        for (c, o) in letters
+            .into_iter()
+            .zip([Outcome::Loss, Outcome::Draw, Outcome::Win])
+        {
+            if self.outcome_codes.insert(c, o).is_some() {
+                return Err(format!("Duplicated outcome letter: {:?}", c));
+            }
+        }
+        Ok(self)
+    }
+
+    /// The classic Rock-Paper-Scissors with the AoC encoding
+    /// ```
+    /// use aoc2022::{Round, Rules};
+    ///
+    /// let rules = Rules::classic();
+    /// let round = Round::from("A Y").unwrap();
+    /// assert_eq!(rules.wrong_score(&round), Some(8));
+    /// assert_eq!(rules.right_score(&round), Some(4));
+    /// ```
+    pub fn classic() -> Self {
+        Self::new(
+            &[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+            &[
+                ("Rock", "Scissors"),
+                ("Scissors", "Paper"),
+                ("Paper", "Rock"),
+            ],
+        )
+        .and_then(|rules| rules.with_encoding("ABC", "XYZ", "XYZ"))
+        .expect("classic rules are valid")
+    }
+
+    /// Rock-Paper-Scissors-Lizard-Spock.
+    /// The opponent uses A-E, the player uses V-Z for shapes and X-Z for outcomes.
+    /// ```
+    /// use aoc2022::{Round, Rules};
+    ///
+    /// let rules = Rules::rpsls();
+    /// let round = Round::from("E V").unwrap(); // Spock vs Rock
+    /// assert_eq!(rules.wrong_score(&round), Some(1));
+    /// assert_eq!(rules.right_score(&round), None);
+    ///
+    /// // Both Paper and Lizard beat Spock, the more valuable one is chosen
+    /// let round = Round::from("E Z").unwrap();
+    /// assert_eq!(rules.right_score(&round), Some(10));
+    /// ```
+    pub fn rpsls() -> Self {
+        Self::new(
+            &[
+                ("Rock", 1),
+                ("Paper", 2),
+                ("Scissors", 3),
+                ("Lizard", 4),
+                ("Spock", 5),
+            ],
+            &[
+                ("Scissors", "Paper"),
+                ("Paper", "Rock"),
+                ("Rock", "Lizard"),
+                ("Lizard", "Spock"),
+                ("Spock", "Scissors"),
+                ("Scissors", "Lizard"),
+                ("Lizard", "Paper"),
+                ("Paper", "Spock"),
+                ("Spock", "Rock"),
+                ("Rock", "Scissors"),
+            ],
+        )
+        .and_then(|rules| rules.with_encoding("ABCDE", "VWXYZ", "XYZ"))
+        .expect("RPSLS rules are valid")
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    pub fn shape_by_name(&self, name: &str) -> Option<usize> {
+        self.shapes.iter().position(|s| s.name == name)
+    }
+
+    pub fn opponent_shape(&self, code: char) -> Option<usize> {
+        self.opponent_codes.get(&code).copied()
+    }
+
+    pub fn player_shape(&self, code: char) -> Option<usize> {
+        self.player_codes.get(&code).copied()
+    }
+
+    pub fn expected_outcome(&self, code: char) -> Option<Outcome> {
+        self.outcome_codes.get(&code).copied()
+    }
+
+    // The outcome of the round for the player
+    pub fn outcome(&self, player: usize, opponent: usize) -> Outcome {
+        if player == opponent {
+            Outcome::Draw
+        } else if self.beats.contains(&(player, opponent)) {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    // The player's score for the round
+    pub fn score(&self, player: usize, opponent: usize) -> u32 {
+        self.shapes[player].score + self.outcome_scores[&self.outcome(player, opponent)]
+    }
+
+    // The shape to show against the opponent to get the outcome.
+    // When several shapes fit, the most valuable one is chosen.
+    pub fn response(&self, opponent: usize, outcome: Outcome) -> Option<usize> {
+        let mut output: Option<usize> = None;
+        for shape in 0..self.shapes.len() {
+            if self.outcome(shape, opponent) != outcome {
+                continue;
+            }
+            match output {
+                Some(best) if self.shapes[best].score >= self.shapes[shape].score => {}
+                _ => output = Some(shape),
+            }
+        }
+        output
+    }
+
+    // The score when the second letter is the player's shape
+    pub fn wrong_score(&self, round: &Round) -> Option<u32> {
+        let opponent = self.opponent_shape(round.0)?;
+        let player = self.player_shape(round.1)?;
+        Some(self.score(player, opponent))
+    }
+
+    // The score when the second letter is the expected outcome
+    pub fn right_score(&self, round: &Round) -> Option<u32> {
+        let opponent = self.opponent_shape(round.0)?;
+        let player = self.response(opponent, self.expected_outcome(round.1)?)?;
+        Some(self.score(player, opponent))
+    }
+
+    fn shape_codes(&self, letters: &str) -> Result<HashMap<char, usize>, String> {
+        let letters: Vec<char> = letters.chars().collect();
+        if letters.len() != self.shapes.len() {
+            return Err(format!(
+                "Expected {} shape letters, got {:?}",
+                self.shapes.len(),
+                letters
+            ));
+        }
+        let mut output = HashMap::with_capacity(letters.len());
+        for (index, c) in letters.into_iter().enumerate() {
+            if output.insert(c, index).is_some() {
+                return Err(format!("Duplicated shape letter: {:?}", c));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// A way to read the player's letters of the guide, along with the total score under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoding<T> {
+    pub mapping: Vec<(char, T)>,
+    pub score: Option<u32>,
+}
+
+/// Total scores of the guide under every possible reading of the player's letters
+#[derive(Debug, Clone)]
+pub struct EncodingAnalysis {
+    pub shapes: Vec<Decoding<usize>>,
+    pub outcomes: Vec<Decoding<Outcome>>,
+}
+impl EncodingAnalysis {
+    pub fn best_shapes(&self) -> Option<&Decoding<usize>> {
+        Self::best(&self.shapes)
+    }
+
+    pub fn worst_shapes(&self) -> Option<&Decoding<usize>> {
+        Self::worst(&self.shapes)
+    }
+
+    pub fn best_outcomes(&self) -> Option<&Decoding<Outcome>> {
+        Self::best(&self.outcomes)
+    }
+
+    pub fn worst_outcomes(&self) -> Option<&Decoding<Outcome>> {
+        Self::worst(&self.outcomes)
+    }
+
+    // The first decoding with the highest score
+    fn best<T>(list: &[Decoding<T>]) -> Option<&Decoding<T>> {
+        let mut output: Option<&Decoding<T>> = None;
+        for d in list.iter().filter(|d| d.score.is_some()) {
+            match output {
+                Some(best) if best.score >= d.score => {}
+                _ => output = Some(d),
+            }
+        }
+        output
+    }
+
+    // The first decoding with the lowest score
+    fn worst<T>(list: &[Decoding<T>]) -> Option<&Decoding<T>> {
+        let mut output: Option<&Decoding<T>> = None;
+        for d in list.iter().filter(|d| d.score.is_some()) {
+            match output {
+                Some(worst) if worst.score <= d.score => {}
+                _ => output = Some(d),
+            }
+        }
+        output
+    }
+}
+
+// All permutations of 0..size in lexicographic order
+fn permutations(size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    let mut output = Vec::new();
+    for head in 0..size {
+        for tail in permutations(size - 1) {
+            let mut p = Vec::with_capacity(size);
+            p.push(head);
+            p.extend(tail.into_iter().map(|i| if i >= head { i + 1 } else { i }));
+            output.push(p);
+        }
+    }
+    output
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round(pub char, pub char);
 impl Round {
     pub fn from(input: &str) -> Option<Self> {
         let mut data = input.split(' ');
-        let left = match data.next()? {
-            "A" => Some('A'),
-            "B" => Some('B'),
-            "C" => Some('C'),
-            _ => None,
-        }?;
-        let right = match data.next()? {
-            "X" => Some('X'),
-            "Y" => Some('Y'),
-            "Z" => Some('Z'),
-            _ => None,
-        }?;
-        Some(Self((left, right)))
-    }
-
-    pub fn wrong_score(&self) -> u32 {
-        match self.0 {
-            ('B', 'X') => 1, // PR
-            ('C', 'Y') => 2, // SP
-            ('A', 'Z') => 3, // RS
-            ('A', 'X') => 4, // RR
-            ('B', 'Y') => 5, // PP
-            ('C', 'Z') => 6, // SS
-            ('C', 'X') => 7, // SR
-            ('A', 'Y') => 8, // RP
-            ('B', 'Z') => 9, // PS
-            _ => panic!(),
-        }
-    }
-
-    pub fn right_score(&self) -> u32 {
-        match self.0 {
-            ('B', 'X') => 1, // PR
-            ('C', 'X') => 2, // SP
-            ('A', 'X') => 3, // RS
-            ('A', 'Y') => 4, // RR
-            ('B', 'Y') => 5, // PP
-            ('C', 'Y') => 6, // SS
-            ('C', 'Z') => 7, // SR
-            ('A', 'Z') => 8, // RP
-            ('B', 'Z') => 9, // PS
-            _ => panic!(),
+        let left = Self::letter(data.next()?)?;
+        let right = Self::letter(data.next()?)?;
+        Some(Self(left, right))
+    }
+
+    fn letter(input: &str) -> Option<char> {
+        let mut chars = input.chars();
+        let c = chars.next()?;
+        match chars.next() {
+            None => Some(c),
+            Some(_) => None,
         }
     }
 }
 
 /// Rock-Scissors-Paper game against an elf
 /// ```
-/// use aoc2022::Game;
+/// use aoc2022::{Game, Rules};
 ///
 /// let game = Game::from("A Y\nB X\nC Z").unwrap();
-/// assert_eq!(game.wrong_score(), 15);
-/// assert_eq!(game.right_score(), 12);
+/// assert_eq!(game.wrong_score(), Some(15));
+/// assert_eq!(game.right_score(), Some(12));
+///
+/// let game = Game::with_rules("A X\nE Y\nD Z", Rules::rpsls()).unwrap();
+/// assert_eq!(game.wrong_score(), Some(18));
+/// assert_eq!(game.right_score(), Some(21));
+///
+/// assert!(Game::from("A Q").is_none());
 /// ```
 #[derive(Debug)]
-pub struct Game(Vec<Round>);
+pub struct Game {
+    rules: Rules,
+    rounds: Vec<Round>,
+}
 impl Game {
     pub fn from(input: &str) -> Option<Self> {
+        Self::with_rules(input, Rules::classic())
+    }
+
+    // Parse the guide whose letters are known to the rules
+    pub fn with_rules(input: &str, rules: Rules) -> Option<Self> {
         let data = input.split('\n');
-        let mut game: Vec<Round> = Vec::new();
-        for round in data {
-            game.push(Round::from(round)?)
+        let mut rounds: Vec<Round> = Vec::new();
+        for line in data {
+            let round = Round::from(line)?;
+            rules.opponent_shape(round.0)?;
+            if rules.player_shape(round.1).is_none() && rules.expected_outcome(round.1).is_none() {
+                return None;
+            }
+            rounds.push(round)
         }
-        Some(Self(game))
+        Some(Self { rules, rounds })
     }
 
     pub fn load_from(path: &str) -> Option<Self> {
@@ -76,15 +434,365 @@ impl Game {
         Self::from(&data)
     }
 
-    pub fn wrong_score(&self) -> u32 {
-        self.0
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    pub fn wrong_score(&self) -> Option<u32> {
+        self.total(|round| self.rules.wrong_score(round))
+    }
+
+    pub fn right_score(&self) -> Option<u32> {
+        self.total(|round| self.rules.right_score(round))
+    }
+
+    /// Score the guide under every permutation of the player's letters,
+    /// read either as shapes or as expected outcomes
+    /// ```
+    /// use aoc2022::{Game, Outcome};
+    ///
+    /// let game = Game::from("A Y\nB X\nC Z").unwrap();
+    /// let analysis = game.analyse_encoding();
+    /// assert_eq!(analysis.shapes.len(), 6);
+    /// assert_eq!(analysis.outcomes.len(), 6);
+    ///
+    /// // The AoC readings are among the permutations
+    /// assert_eq!(analysis.shapes[0].mapping, vec![('X', 0), ('Y', 1), ('Z', 2)]);
+    /// assert_eq!(analysis.shapes[0].score, Some(15));
+    /// assert_eq!(analysis.outcomes[0].score, Some(12));
+    ///
+    /// let best = analysis.best_shapes().unwrap();
+    /// assert_eq!(best.mapping, vec![('X', 2), ('Y', 1), ('Z', 0)]);
+    /// assert_eq!(best.score, Some(24));
+    /// assert_eq!(analysis.worst_shapes().unwrap().score, Some(6));
+    ///
+    /// let best = analysis.best_outcomes().unwrap();
+    /// assert_eq!(best.mapping, vec![('X', Outcome::Win), ('Y', Outcome::Loss), ('Z', Outcome::Draw)]);
+    /// assert_eq!(best.score, Some(18));
+    /// assert_eq!(analysis.worst_outcomes().unwrap().score, Some(12));
+    /// ```
+    pub fn analyse_encoding(&self) -> EncodingAnalysis {
+        let mut letters: Vec<char> = self.rules.player_codes.keys().copied().collect();
+        letters.sort();
+        let mut shapes = Vec::new();
+        for p in permutations(letters.len()) {
+            let mut rules = self.rules.clone();
+            rules.player_codes = letters.iter().copied().zip(p).collect();
+            shapes.push(Decoding {
+                mapping: Self::sorted(&rules.player_codes),
+                score: self.total(|round| rules.wrong_score(round)),
+            });
+        }
+
+        let mut letters: Vec<char> = self.rules.outcome_codes.keys().copied().collect();
+        letters.sort();
+        let all = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+        let mut outcomes = Vec::new();
+        for p in permutations(letters.len()) {
+            let mut rules = self.rules.clone();
+            rules.outcome_codes = letters
+                .iter()
+                .copied()
+                .zip(p.into_iter().map(|i| all[i]))
+                .collect();
+            outcomes.push(Decoding {
+                mapping: Self::sorted(&rules.outcome_codes),
+                score: self.total(|round| rules.right_score(round)),
+            });
+        }
+
+        EncodingAnalysis { shapes, outcomes }
+    }
+
+    /// Play a seeded match between two strategies under the rules of the game
+    /// ```
+    /// use aoc2022::{AlwaysRock, Cyclic, Game, Outcome};
+    ///
+    /// let game = Game::from("A Y\nB X\nC Z").unwrap();
+    /// let report = game.play_match(&AlwaysRock, &Cyclic, 3, 42);
+    /// assert_eq!(report.rounds.len(), 3);
+    /// assert_eq!(report.rounds[1].right, 1);
+    /// assert_eq!((report.left_total, report.right_total), (12, 15));
+    /// assert_eq!(report.outcome(), Outcome::Loss);
+    ///
+    /// // The same seed gives the same match
+    /// use aoc2022::FrequencyCounter;
+    /// let a = game.play_match(&FrequencyCounter, &FrequencyCounter, 20, 7);
+    /// let b = game.play_match(&FrequencyCounter, &FrequencyCounter, 20, 7);
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn play_match(
+        &self,
+        left: &dyn Strategy,
+        right: &dyn Strategy,
+        rounds: usize,
+        seed: u64,
+    ) -> MatchReport {
+        let mut rng = Rng::new(seed);
+        let mut left_history = Vec::with_capacity(rounds);
+        let mut right_history = Vec::with_capacity(rounds);
+        let mut report = MatchReport {
+            rounds: Vec::with_capacity(rounds),
+            left_total: 0,
+            right_total: 0,
+        };
+
+        for _ in 0..rounds {
+            let l = left.choose(&self.rules, &left_history, &mut rng);
+            let r = right.choose(&self.rules, &right_history, &mut rng);
+            let round = MatchRound {
+                left: l,
+                right: r,
+                left_score: self.rules.score(l, r),
+                right_score: self.rules.score(r, l),
+            };
+            report.left_total += round.left_score;
+            report.right_total += round.right_score;
+            report.rounds.push(round);
+            left_history.push((l, r));
+            right_history.push((r, l));
+        }
+        report
+    }
+
+    /// Play a round-robin tournament where every strategy meets every other once.
+    /// The table is sorted by points, then by total score.
+    /// ```
+    /// use aoc2022::{AlwaysRock, Cyclic, FollowGuide, FrequencyCounter, Game, Strategy};
+    ///
+    /// let game = Game::from("A Y\nB X\nC Z").unwrap();
+    /// let guide = FollowGuide::new(&game);
+    /// let players: Vec<&dyn Strategy> = vec![&AlwaysRock, &Cyclic, &FrequencyCounter, &guide];
+    /// let table = game.tournament(&players, 100, 1);
+    ///
+    /// assert_eq!(table.len(), 4);
+    /// // The guide always recommends Rock here, so both rock players draw
+    /// assert_eq!(table[0].name, "cyclic");
+    /// assert_eq!(table[0].wins, 3);
+    /// assert_eq!(table[2].draws, 1);
+    /// assert_eq!(table[3].draws, 1);
+    /// for row in table.iter() {
+    ///     assert_eq!(row.wins + row.draws + row.losses, 3);
+    /// }
+    /// ```
+    pub fn tournament(&self, players: &[&dyn Strategy], rounds: usize, seed: u64) -> Vec<Standing> {
+        let mut table: Vec<Standing> = players
             .iter()
-            .fold(0, |acc, round| acc + round.wrong_score())
+            .map(|p| Standing {
+                name: p.name(),
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                score: 0,
+            })
+            .collect();
+
+        let mut match_seed = seed;
+        for l in 0..players.len() {
+            for r in (l + 1)..players.len() {
+                let report = self.play_match(players[l], players[r], rounds, match_seed);
+                match_seed = match_seed.wrapping_add(1);
+
+                table[l].score += report.left_total;
+                table[r].score += report.right_total;
+                match report.outcome() {
+                    Outcome::Win => {
+                        table[l].wins += 1;
+                        table[r].losses += 1;
+                    }
+                    Outcome::Draw => {
+                        table[l].draws += 1;
+                        table[r].draws += 1;
+                    }
+                    Outcome::Loss => {
+                        table[l].losses += 1;
+                        table[r].wins += 1;
+                    }
+                }
+            }
+        }
+
+        table.sort_by(|a, b| {
+            b.points()
+                .cmp(&a.points())
+                .then(b.score.cmp(&a.score))
+                .then(a.name.cmp(&b.name))
+        });
+        table
+    }
+
+    fn total<F: Fn(&Round) -> Option<u32>>(&self, score: F) -> Option<u32> {
+        let mut output = 0;
+        for round in self.rounds.iter() {
+            output += score(round)?;
+        }
+        Some(output)
+    }
+
+    fn sorted<T: Copy>(codes: &HashMap<char, T>) -> Vec<(char, T)> {
+        let mut output: Vec<(char, T)> = codes.iter().map(|(&c, &t)| (c, t)).collect();
+        output.sort_by_key(|&(c, _)| c);
+        output
+    }
+}
+
+/// A tiny deterministic generator (xorshift) to make matches reproducible
+/// ```
+/// use aoc2022::Rng;
+///
+/// let mut rng = Rng::new(0x9E37_79B9_7F4A_7C15);
+/// let numbers: Vec<usize> = (0..20).map(|_| rng.below(3)).collect();
+/// assert!(numbers.iter().any(|&n| n != 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        // xorshift gets stuck at zero
+        Self(if state == 0 { 1 } else { state })
     }
 
-    pub fn right_score(&self) -> u32 {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
         self.0
-            .iter()
-            .fold(0, |acc, round| acc + round.right_score())
+    }
+
+    // A number in the range 0..limit
+    pub fn below(&mut self, limit: usize) -> usize {
+        (self.next_u64() % limit as u64) as usize
+    }
+}
+
+/// The way a player chooses shapes.
+/// The history lists (own, opponent's) shapes of previous rounds.
+pub trait Strategy {
+    fn name(&self) -> String;
+    fn choose(&self, rules: &Rules, history: &[(usize, usize)], rng: &mut Rng) -> usize;
+}
+
+/// Shows Rock every round (or the first shape if there is no Rock)
+pub struct AlwaysRock;
+impl Strategy for AlwaysRock {
+    fn name(&self) -> String {
+        "always-rock".into()
+    }
+
+    fn choose(&self, rules: &Rules, _: &[(usize, usize)], _: &mut Rng) -> usize {
+        rules.shape_by_name("Rock").unwrap_or(0)
+    }
+}
+
+/// Goes through all shapes in order
+pub struct Cyclic;
+impl Strategy for Cyclic {
+    fn name(&self) -> String {
+        "cyclic".into()
+    }
+
+    fn choose(&self, rules: &Rules, history: &[(usize, usize)], _: &mut Rng) -> usize {
+        history.len() % rules.shapes().len()
+    }
+}
+
+/// Beats the shape the opponent has shown most often so far.
+/// Ties are broken at random.
+pub struct FrequencyCounter;
+impl Strategy for FrequencyCounter {
+    fn name(&self) -> String {
+        "frequency-counter".into()
+    }
+
+    fn choose(&self, rules: &Rules, history: &[(usize, usize)], rng: &mut Rng) -> usize {
+        let mut counter = vec![0; rules.shapes().len()];
+        for &(_, opponent) in history {
+            counter[opponent] += 1;
+        }
+        let max = counter.iter().max().copied().unwrap_or(0);
+        let candidates: Vec<usize> = (0..counter.len()).filter(|&s| counter[s] == max).collect();
+        let expected = candidates[rng.below(candidates.len())];
+        rules.response(expected, Outcome::Win).unwrap_or(expected)
+    }
+}
+
+/// Plays the shapes recommended by the guide (read as expected outcomes),
+/// starting over when the guide runs out
+pub struct FollowGuide(Vec<usize>);
+impl FollowGuide {
+    pub fn new(game: &Game) -> Self {
+        let rules = game.rules();
+        let mut shapes = Vec::with_capacity(game.rounds().len());
+        for round in game.rounds() {
+            let recommended = rules
+                .opponent_shape(round.0)
+                .zip(rules.expected_outcome(round.1))
+                .and_then(|(opponent, outcome)| rules.response(opponent, outcome))
+                .or(rules.player_shape(round.1));
+            if let Some(shape) = recommended {
+                shapes.push(shape);
+            }
+        }
+        Self(shapes)
+    }
+}
+impl Strategy for FollowGuide {
+    fn name(&self) -> String {
+        "follow-the-guide".into()
+    }
+
+    fn choose(&self, rules: &Rules, history: &[(usize, usize)], _: &mut Rng) -> usize {
+        if self.0.is_empty() {
+            rules.shape_by_name("Rock").unwrap_or(0)
+        } else {
+            self.0[history.len() % self.0.len()]
+        }
+    }
+}
+
+/// A single round of the match between the left and right players
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchRound {
+    pub left: usize,
+    pub right: usize,
+    pub left_score: u32,
+    pub right_score: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchReport {
+    pub rounds: Vec<MatchRound>,
+    pub left_total: u32,
+    pub right_total: u32,
+}
+impl MatchReport {
+    // The outcome of the whole match for the left player
+    pub fn outcome(&self) -> Outcome {
+        match self.left_total.cmp(&self.right_total) {
+            std::cmp::Ordering::Less => Outcome::Loss,
+            std::cmp::Ordering::Equal => Outcome::Draw,
+            std::cmp::Ordering::Greater => Outcome::Win,
+        }
+    }
+}
+
+/// The row of the tournament table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub score: u32,
+}
+impl Standing {
+    // 3 points for a win, 1 for a draw
+    pub fn points(&self) -> u32 {
+        3 * self.wins + self.draws
     }
 }
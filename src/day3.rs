@@ -1,80 +1,341 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fs;
-use std::hash::Hash;
+
+// Priorities of ASCII chars, 0 stands for unknown ones
+const PRIORITIES: [u8; 128] = {
+    let mut table = [0; 128];
+    let mut i = 0;
+    while i < 26 {
+        table[b'a' as usize + i] = i as u8 + 1;
+        table[b'A' as usize + i] = i as u8 + 27;
+        i += 1;
+    }
+    table
+};
+
+// Chars by priority, the inverse of PRIORITIES
+const ITEMS: [u8; 53] = {
+    let mut table = [0; 53];
+    let mut i = 0;
+    while i < 26 {
+        table[i + 1] = b'a' + i as u8;
+        table[i + 27] = b'A' + i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// The table of item priorities.
+/// Priorities go from 1 to 128, so that every item type takes a bit of the mask.
+/// ```
+/// use aoc2022::Priorities;
+///
+/// let aoc = Priorities::aoc();
+/// assert_eq!(aoc.priority('p'), Some(16));
+/// assert_eq!(aoc.priority('1'), None);
+///
+/// let custom = Priorities::from("# digits go first\n0 1\n1 2\na 3").unwrap();
+/// assert_eq!(custom.priority('1'), Some(2));
+/// assert_eq!(custom.priority('a'), Some(3));
+/// assert_eq!(custom.priority('b'), None);
+///
+/// let error = Priorities::from("a 1\nb 1").unwrap_err();
+/// assert_eq!(error, "Line 2: Priority 1 is already taken");
+///
+/// // Lowercase letters go first, then uppercase ones, then the rest
+/// let unicode = Priorities::unicode("äbÄ1a").unwrap();
+/// assert_eq!(unicode.priority('a'), Some(1));
+/// assert_eq!(unicode.priority('ä'), Some(3));
+/// assert_eq!(unicode.priority('Ä'), Some(4));
+/// assert_eq!(unicode.priority('1'), Some(5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Priorities {
+    ascii: [u8; 128],
+    others: HashMap<char, u8>,
+}
+impl Priorities {
+    pub const MAX: u32 = 128;
+
+    // The AoC table: a-z are 1-26, A-Z are 27-52
+    pub fn aoc() -> Self {
+        Self {
+            ascii: PRIORITIES,
+            others: HashMap::new(),
+        }
+    }
+
+    // Parse the table from lines like: "a 1"
+    pub fn from(input: &str) -> Result<Self, String> {
+        let mut output = Self {
+            ascii: [0; 128],
+            others: HashMap::new(),
+        };
+        for (index, line) in input.split('\n').enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let (item, priority) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(item), Some(priority), None) => (item, priority),
+                _ => return Err(format!("Line {}: Cannot parse {:?}", index + 1, line)),
+            };
+            let mut chars = item.chars();
+            let item = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(format!(
+                        "Line {}: {:?} is not a single char",
+                        index + 1,
+                        item
+                    ))
+                }
+            };
+            let priority = priority
+                .parse()
+                .map_err(|_| format!("Line {}: Cannot parse priority {:?}", index + 1, priority))?;
+            output
+                .insert(item, priority)
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+        }
+        Ok(output)
+    }
+
+    pub fn load_from(path: &str) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from(&data)
+    }
+
+    // Order all chars of the input: lowercase letters, then uppercase ones, then the rest.
+    // Chars within every group are ordered by their code points.
+    pub fn unicode(input: &str) -> Result<Self, String> {
+        let mut chars: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+        chars.sort_by_key(|&c| {
+            let group = if c.is_lowercase() {
+                0
+            } else if c.is_uppercase() {
+                1
+            } else {
+                2
+            };
+            (group, c)
+        });
+        chars.dedup();
+
+        let mut output = Self {
+            ascii: [0; 128],
+            others: HashMap::new(),
+        };
+        for (index, c) in chars.into_iter().enumerate() {
+            output.insert(c, index as u32 + 1)?;
+        }
+        Ok(output)
+    }
+
+    pub fn priority(&self, item: char) -> Option<u32> {
+        let p = if item.is_ascii() {
+            self.ascii[item as usize]
+        } else {
+            *self.others.get(&item)?
+        };
+        if p == 0 {
+            None
+        } else {
+            Some(p as u32)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ascii.iter().filter(|&&p| p > 0).count() + self.others.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&mut self, item: char, priority: u32) -> Result<(), String> {
+        if priority == 0 || priority > Self::MAX {
+            return Err(format!(
+                "Priority {} is out of range 1..={}",
+                priority,
+                Self::MAX
+            ));
+        }
+        if self.priority(item).is_some() {
+            return Err(format!("Item {:?} is already listed", item));
+        }
+        let taken = self.ascii.iter().any(|&p| p as u32 == priority)
+            || self.others.values().any(|&p| p as u32 == priority);
+        if taken {
+            return Err(format!("Priority {} is already taken", priority));
+        }
+
+        if item.is_ascii() {
+            self.ascii[item as usize] = priority as u8;
+        } else {
+            self.others.insert(item, priority as u8);
+        }
+        Ok(())
+    }
+}
 
 /// The item in the rucksack
 /// ```
 /// use aoc2022::Item;
 ///
 /// let item = Item('p');
-/// assert_eq!(item.score(), 16);
+/// assert_eq!(item.score(), Some(16));
+/// assert_eq!(Item('1').score(), None);
+///
+/// assert_eq!(Item::from_priority(42), Some(Item('P')));
 /// ```
 #[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
 pub struct Item(pub char);
 impl Item {
-    pub fn score(&self) -> u32 {
-        let mut lower = 'a'..='z';
-        let mut upper = 'A'..='Z';
-        if let Some(p) = lower.position(|l| l == self.0) {
-            p as u32 + 1
-        } else {
-            upper.position(|u| u == self.0).unwrap() as u32 + 27
+    pub fn from_priority(priority: u32) -> Option<Self> {
+        match priority {
+            1..=52 => Some(Self(ITEMS[priority as usize] as char)),
+            _ => None,
+        }
+    }
+
+    // The priority in the AoC table
+    pub fn score(&self) -> Option<u32> {
+        match PRIORITIES.get(self.0 as usize) {
+            Some(&p) if p > 0 => Some(p as u32),
+            _ => None,
         }
     }
 }
 
+/// Items along with the mask of their types
+/// ```
+/// use aoc2022::{Item, Items, Priorities};
+///
+/// let items = Items::from("abca").unwrap();
+/// assert_eq!(items.len(), 4);
+/// assert_eq!(items.mask(), 0b111);
+/// assert_eq!(items.uniq_items(), vec![Item('a'), Item('b'), Item('c')]);
+/// assert!(Items::from("ab1").is_none());
+///
+/// let error = Items::parse("ab1", &Priorities::aoc()).unwrap_err();
+/// assert_eq!(error, "Unknown item '1' at position 3");
+/// ```
 #[derive(Debug, Clone)]
-pub struct Items(Vec<Item>);
+pub struct Items {
+    list: Vec<Item>,
+    ranks: Vec<u32>,
+    mask: u128,
+}
 impl Items {
-    pub fn from(input: &str) -> Self {
-        let mut output = Vec::with_capacity(input.len());
-        for c in input.chars() {
-            output.push(Item(c))
+    // Items with the AoC priorities
+    pub fn from(input: &str) -> Option<Self> {
+        Self::parse(input, &Priorities::aoc()).ok()
+    }
+
+    pub fn parse(input: &str, priorities: &Priorities) -> Result<Self, String> {
+        let mut list = Vec::with_capacity(input.len());
+        let mut ranks = Vec::with_capacity(input.len());
+        let mut mask = 0;
+        for (index, c) in input.chars().enumerate() {
+            let rank = priorities.priority(c).ok_or(format!(
+                "Unknown item {:?} at position {}",
+                c,
+                index + 1
+            ))?;
+            mask |= 1 << (rank - 1);
+            list.push(Item(c));
+            ranks.push(rank);
         }
-        Self(output)
+        Ok(Self { list, ranks, mask })
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.list.len()
     }
 
-    pub fn uniq_items(&self) -> HashSet<Item> {
-        let mut collector: HashSet<Item> = HashSet::with_capacity(self.0.len());
-        for i in self.0.iter() {
-            collector.insert(i.clone());
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn mask(&self) -> u128 {
+        self.mask
+    }
+
+    // Item types in the order of their priorities
+    pub fn uniq_items(&self) -> Vec<Item> {
+        Self::items_of(self.mask, [self])
+    }
+
+    // Item types of the mask found among the members, by priority
+    fn items_of<'a, I: IntoIterator<Item = &'a Items>>(mask: u128, members: I) -> Vec<Item> {
+        let mut found: Vec<(u32, Item)> = Vec::with_capacity(mask.count_ones() as usize);
+        let mut left = mask;
+        for items in members {
+            for (&item, &rank) in items.list.iter().zip(items.ranks.iter()) {
+                if left == 0 {
+                    break;
+                }
+                let bit = 1 << (rank - 1);
+                if left & bit != 0 {
+                    found.push((rank, item));
+                    left &= !bit;
+                }
+            }
         }
-        collector
+        found.sort_by_key(|&(rank, _)| rank);
+        found.into_iter().map(|(_, item)| item).collect()
     }
 }
 
 #[derive(Debug)]
 pub struct Group(pub Vec<Items>);
 impl Group {
+    // The mask of item types shared by all members of the group
+    pub fn common_mask(&self) -> u128 {
+        if self.0.is_empty() {
+            return 0;
+        }
+        self.0.iter().fold(u128::MAX, |a, i| a & i.mask())
+    }
+
+    // The shared item with the lowest priority
     pub fn badge(&self) -> Option<Item> {
-        let group_size = self.0.len();
-        let total_size = self.0.iter().fold(0, |a, i| a + i.len());
+        let mask = self.common_mask();
+        Items::items_of(mask & mask.wrapping_neg(), self.0.iter())
+            .first()
+            .copied()
+    }
 
-        let mut counter: HashMap<Item, usize> = HashMap::with_capacity(total_size);
-        for items in self.0.iter() {
-            for i in items.uniq_items() {
-                if let Some(c) = counter.get(&i) {
-                    counter.insert(i, c + 1);
-                } else {
-                    counter.insert(i, 1);
-                }
-            }
-        }
+    pub fn score(&self) -> Option<u32> {
+        lowest_priority(self.common_mask())
+    }
 
-        for (item, c) in counter {
-            if c == group_size {
-                return Some(item);
-            }
+    // All item types shared by every member of the group, by priority
+    pub fn badge_candidates(&self) -> Vec<Item> {
+        Items::items_of(self.common_mask(), self.0.iter())
+    }
+
+    // Item types found in more than one member of the group, by priority
+    pub fn repeated_items(&self) -> Vec<Item> {
+        let mut seen = 0;
+        let mut repeated = 0;
+        for items in self.0.iter() {
+            repeated |= seen & items.mask();
+            seen |= items.mask();
         }
-        None
+        Items::items_of(repeated, self.0.iter())
     }
+}
 
-    pub fn score(&self) -> Option<u32> {
-        Some(self.badge()?.score())
+// The priority of the lowest item type in the mask
+fn lowest_priority(mask: u128) -> Option<u32> {
+    if mask == 0 {
+        None
+    } else {
+        Some(mask.trailing_zeros() + 1)
     }
 }
 
@@ -82,29 +343,32 @@ impl Group {
 /// ```
 /// use aoc2022::{Rucksack, Item};
 ///
-/// let rucksack = Rucksack::from("vJrwpWtwJgWrhcsFMMfFFhFp");
+/// let rucksack = Rucksack::from("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('p')));
 /// assert_eq!(rucksack.score(), Some(16));
 ///
-/// let rucksack = Rucksack::from("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL");
+/// let rucksack = Rucksack::from("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('L')));
 /// assert_eq!(rucksack.score(), Some(38));
 ///
-/// let rucksack = Rucksack::from("PmmdzqPrVvPwwTWBwg");
+/// let rucksack = Rucksack::from("PmmdzqPrVvPwwTWBwg").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('P')));
 /// assert_eq!(rucksack.score(), Some(42));
 ///
-/// let rucksack = Rucksack::from("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn");
+/// let rucksack = Rucksack::from("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('v')));
 /// assert_eq!(rucksack.score(), Some(22));
 ///
-/// let rucksack = Rucksack::from("ttgJtRGJQctTZtZT");
+/// let rucksack = Rucksack::from("ttgJtRGJQctTZtZT").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('t')));
 /// assert_eq!(rucksack.score(), Some(20));
 ///
-/// let rucksack = Rucksack::from("CrZsJsPPZsGzwwsLwLmpwMDw");
+/// let rucksack = Rucksack::from("CrZsJsPPZsGzwwsLwLmpwMDw").unwrap();
 /// assert_eq!(rucksack.badge(), Some(Item('s')));
 /// assert_eq!(rucksack.score(), Some(19));
+///
+/// assert!(Rucksack::from("a1b1").is_none());
+/// assert!(Rucksack::from("abc").is_none());
 /// ```
 #[derive(Debug)]
 pub struct Rucksack {
@@ -112,15 +376,62 @@ pub struct Rucksack {
     pub compartments: Group,
 }
 impl Rucksack {
-    pub fn from(input: &str) -> Self {
-        let size = input.len() / 2;
-        let items = Items::from(input);
-        let (l, r) = input.split_at(size);
-        let compartments = Group(Vec::from([Items::from(l), Items::from(r)]));
-        Self {
-            items,
-            compartments,
+    // Split the line into two halves
+    pub fn from(input: &str) -> Option<Self> {
+        Self::with_compartments(input, 2).ok()
+    }
+
+    /// Split the line into the given number of equal compartments
+    /// ```
+    /// use aoc2022::{Item, Rucksack};
+    ///
+    /// let rucksack = Rucksack::with_compartments("abcdaf", 3).unwrap();
+    /// assert_eq!(rucksack.compartments.0.len(), 3);
+    /// assert_eq!(rucksack.badge(), None);
+    ///
+    /// let rucksack = Rucksack::with_compartments("abcabdaef", 3).unwrap();
+    /// assert_eq!(rucksack.badge(), Some(Item('a')));
+    ///
+    /// let error = Rucksack::with_compartments("abcde", 2).unwrap_err();
+    /// assert_eq!(error, "Cannot split 5 items into 2 equal compartments");
+    /// ```
+    pub fn with_compartments(input: &str, count: usize) -> Result<Self, String> {
+        Self::parse(input, count, &Priorities::aoc())
+    }
+
+    /// Split the line into equal compartments, rejecting items unknown to the table
+    /// ```
+    /// use aoc2022::{Item, Priorities, Rucksack};
+    ///
+    /// let priorities = Priorities::unicode("абвгАБВГ").unwrap();
+    /// let rucksack = Rucksack::parse("абБгБв", 2, &priorities).unwrap();
+    /// assert_eq!(rucksack.badge(), Some(Item('Б')));
+    /// assert_eq!(rucksack.score(), Some(6));
+    ///
+    /// let error = Rucksack::parse("абвБгД", 2, &priorities).unwrap_err();
+    /// assert_eq!(error, "Unknown item 'Д' at position 6");
+    /// ```
+    pub fn parse(input: &str, count: usize, priorities: &Priorities) -> Result<Self, String> {
+        let items = Items::parse(input, priorities)?;
+        let chars: Vec<char> = input.chars().collect();
+        if count == 0 || !chars.len().is_multiple_of(count) {
+            return Err(format!(
+                "Cannot split {} items into {} equal compartments",
+                chars.len(),
+                count
+            ));
         }
+
+        let size = chars.len() / count;
+        let mut compartments = Vec::with_capacity(count);
+        for part in 0..count {
+            let line: String = chars[part * size..(part + 1) * size].iter().collect();
+            compartments.push(Items::parse(&line, priorities)?);
+        }
+        Ok(Self {
+            items,
+            compartments: Group(compartments),
+        })
     }
 
     pub fn badge(&self) -> Option<Item> {
@@ -128,21 +439,327 @@ impl Rucksack {
     }
 
     pub fn score(&self) -> Option<u32> {
-        Some(self.badge()?.score())
+        self.compartments.score()
+    }
+
+    // Every item type packed into more than one compartment
+    pub fn misplaced(&self) -> Vec<Item> {
+        self.compartments.repeated_items()
+    }
+
+    /// Find the fewest item moves after which no item type is packed
+    /// into more than one compartment, while every compartment keeps its size.
+    /// Moved in items take the places of the moved out ones.
+    /// The search is limited in steps, so for many compartments it can stop
+    /// with a plan not proven to be the shortest, or with no plan at all.
+    /// ```
+    /// use aoc2022::{Item, Move, Rucksack};
+    ///
+    /// let rucksack = Rucksack::from("abcadc").unwrap();
+    /// let repacking = rucksack.repack().unwrap();
+    /// assert_eq!(repacking.moves, vec![
+    ///     Move { item: Item('c'), from: 0, to: 1 },
+    ///     Move { item: Item('a'), from: 1, to: 0 },
+    /// ]);
+    /// assert_eq!(repacking.compartments, vec!["aba", "cdc"]);
+    /// assert!(repacking.optimal);
+    ///
+    /// // Moving 'p' out needs another item to take its place
+    /// let rucksack = Rucksack::from("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap();
+    /// let repacking = rucksack.repack().unwrap();
+    /// assert_eq!(repacking.moves.len(), 2);
+    /// let repacked = Rucksack::from(&repacking.compartments.concat()).unwrap();
+    /// assert_eq!(repacked.misplaced(), vec![]);
+    ///
+    /// // Three items of the same type do not fit a compartment of two
+    /// let rucksack = Rucksack::from("aaab").unwrap();
+    /// assert!(rucksack.repack().is_err());
+    ///
+    /// // The search is limited, so larger rucksacks take bounded time
+    /// let letters: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+    /// let line: String = (0..120).map(|i| letters[(i * i + i / 3) % 29]).collect();
+    /// let repacking = Rucksack::with_compartments(&line, 6).unwrap().repack().unwrap();
+    /// assert_eq!(repacking.moves.len(), 75);
+    /// assert!(repacking.optimal);
+    ///
+    /// let line: String = (0..120).map(|i| letters[(i * 11 + i / 7) % 30]).collect();
+    /// let error = Rucksack::with_compartments(&line, 6).unwrap().repack().unwrap_err();
+    /// assert_eq!(error, "No repacking found within 1000000 steps");
+    /// ```
+    pub fn repack(&self) -> Result<Repacking, String> {
+        let parts: Vec<&Vec<Item>> = self.compartments.0.iter().map(|i| &i.list).collect();
+        let caps: Vec<usize> = parts.iter().map(|p| p.len()).collect();
+
+        let mut counts: HashMap<Item, (u32, Vec<usize>)> = HashMap::new();
+        for (index, part) in self.compartments.0.iter().enumerate() {
+            for (&item, &rank) in part.list.iter().zip(part.ranks.iter()) {
+                counts
+                    .entry(item)
+                    .or_insert_with(|| (rank, vec![0; parts.len()]))
+                    .1[index] += 1;
+            }
+        }
+        let mut types: Vec<(Item, u32, Vec<usize>)> = counts
+            .into_iter()
+            .map(|(item, (rank, c))| (item, rank, c))
+            .collect();
+        types.sort_by_key(|&(item, rank, _)| (rank, item.0));
+
+        let mut search = Packing::new(&types, caps.clone());
+        search.branch(0, 0);
+        let optimal = search.steps > 0;
+        if search.best.is_empty() && !types.is_empty() {
+            return Err(if optimal {
+                format!(
+                    "Cannot repack {} items into compartments of {:?}",
+                    self.items.len(),
+                    caps
+                )
+            } else {
+                format!("No repacking found within {} steps", PACKING_STEPS)
+            });
+        }
+        let targets: HashMap<Item, usize> = search
+            .order
+            .iter()
+            .zip(search.best.iter())
+            .map(|(&index, &target)| (types[index].0, target))
+            .collect();
+
+        let mut moves = Vec::new();
+        let mut slots: Vec<Vec<usize>> = vec![vec![]; parts.len()];
+        for (from, part) in parts.iter().enumerate() {
+            for (position, item) in part.iter().enumerate() {
+                let to = targets[item];
+                if to != from {
+                    moves.push(Move {
+                        item: *item,
+                        from,
+                        to,
+                    });
+                    slots[from].push(position);
+                }
+            }
+        }
+
+        let mut layout: Vec<Vec<Item>> = parts.iter().map(|p| p.to_vec()).collect();
+        let mut filled = vec![0; parts.len()];
+        for m in moves.iter() {
+            layout[m.to][slots[m.to][filled[m.to]]] = m.item;
+            filled[m.to] += 1;
+        }
+
+        Ok(Repacking {
+            optimal,
+            moves,
+            compartments: layout
+                .into_iter()
+                .map(|items| items.into_iter().map(|i| i.0).collect())
+                .collect(),
+        })
     }
 }
 
+/// The move of a single item between compartments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub item: Item,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The plan to repack the rucksack along with the resulting compartments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repacking {
+    pub moves: Vec<Move>,
+    pub compartments: Vec<String>,
+    // false when the search ran out of steps before proving there are no fewer moves
+    pub optimal: bool,
+}
+
+// The number of branches the search may try before giving up
+const PACKING_STEPS: usize = 1_000_000;
+
+// Branch and bound search for the compartment of every item type.
+// The largest types are placed first, every one into the compartment
+// holding most of its items, so the first complete choice is close to the best one.
+struct Packing<'a> {
+    // item types with the number of items in every compartment
+    types: &'a [(Item, u32, Vec<usize>)],
+    // indices of types from the largest one
+    order: Vec<usize>,
+    // the fewest moves needed for the types from the index on
+    bound: Vec<usize>,
+    free: Vec<usize>,
+    chosen: Vec<usize>,
+    best: Vec<usize>,
+    best_cost: usize,
+    steps: usize,
+}
+impl<'a> Packing<'a> {
+    fn new(types: &'a [(Item, u32, Vec<usize>)], caps: Vec<usize>) -> Self {
+        let mut order: Vec<usize> = (0..types.len()).collect();
+        order.sort_by_key(|&index| Reverse(types[index].2.iter().sum::<usize>()));
+        let mut bound = vec![0; types.len() + 1];
+        for index in (0..types.len()).rev() {
+            let counts = &types[order[index]].2;
+            let most = counts.iter().max().copied().unwrap_or(0);
+            bound[index] = bound[index + 1] + counts.iter().sum::<usize>() - most;
+        }
+        Self {
+            types,
+            order,
+            bound,
+            free: caps,
+            chosen: Vec::with_capacity(types.len()),
+            best: vec![],
+            best_cost: usize::MAX,
+            steps: PACKING_STEPS,
+        }
+    }
+
+    fn branch(&mut self, index: usize, cost: usize) {
+        if self.steps == 0 || cost + self.bound[index] >= self.best_cost {
+            return;
+        }
+        self.steps -= 1;
+        if index == self.types.len() {
+            self.best_cost = cost;
+            self.best = self.chosen.clone();
+            return;
+        }
+
+        let counts = &self.types[self.order[index]].2;
+        let total: usize = counts.iter().sum();
+        let mut targets: Vec<usize> = (0..counts.len())
+            .filter(|&target| self.free[target] >= total)
+            .collect();
+        targets.sort_by_key(|&target| Reverse(counts[target]));
+        for target in targets {
+            self.free[target] -= total;
+            self.chosen.push(target);
+            self.branch(index + 1, cost + total - counts[target]);
+            self.chosen.pop();
+            self.free[target] += total;
+        }
+    }
+}
+
+/// Items of the rucksack found in more than one compartment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RucksackReport {
+    pub line: usize,
+    pub misplaced: Vec<Item>,
+}
+
+/// Items shared by all rucksacks of the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupReport {
+    pub lines: Vec<usize>,
+    pub candidates: Vec<Item>,
+}
+impl GroupReport {
+    // Either no badge or several candidates for it
+    pub fn is_suspicious(&self) -> bool {
+        self.candidates.len() != 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub rucksacks: Vec<RucksackReport>,
+    pub groups: Vec<GroupReport>,
+}
+impl Diagnostics {
+    // Rucksacks with no misplaced item or several ones
+    pub fn suspicious_rucksacks(&self) -> Vec<&RucksackReport> {
+        self.rucksacks
+            .iter()
+            .filter(|r| r.misplaced.len() != 1)
+            .collect()
+    }
+
+    pub fn suspicious_groups(&self) -> Vec<&GroupReport> {
+        self.groups.iter().filter(|g| g.is_suspicious()).collect()
+    }
+}
+
+/// The list of rucksacks
+/// ```
+/// use aoc2022::Cargo;
+///
+/// let cargo = Cargo::from("vJrwpWtwJgWrhcsFMMfFFhFp\n\
+/// jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\n\
+/// PmmdzqPrVvPwwTWBwg\n\
+/// wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\n\
+/// ttgJtRGJQctTZtZT\n\
+/// CrZsJsPPZsGzwwsLwLmpwMDw"
+/// ).unwrap();
+/// assert_eq!(cargo.individuals_score(), Some(157));
+/// assert_eq!(cargo.groups_score(), Some(70));
+///
+/// let error = Cargo::with_layout("abcd\nabcd", 2, 3).unwrap_err();
+/// assert_eq!(error, "Cannot split 2 rucksacks into groups of 3");
+///
+/// let error = Cargo::with_layout("abcd\nabcde", 2, 2).unwrap_err();
+/// assert_eq!(error, "Line 2: Cannot split 5 items into 2 equal compartments");
+///
+/// let error = Cargo::with_layout("abcd\nab12", 2, 2).unwrap_err();
+/// assert_eq!(error, "Line 2: Unknown item '1' at position 3");
+/// ```
 #[derive(Debug)]
-pub struct Cargo(Vec<Rucksack>);
+pub struct Cargo {
+    rucksacks: Vec<Rucksack>,
+    group_size: usize,
+}
 impl Cargo {
+    // Rucksacks with 2 compartments grouped by 3
+    pub fn from(input: &str) -> Option<Self> {
+        Self::with_layout(input, 2, 3).ok()
+    }
+
+    pub fn with_layout(
+        input: &str,
+        compartments: usize,
+        group_size: usize,
+    ) -> Result<Self, String> {
+        Self::parse(input, compartments, group_size, &Priorities::aoc())
+    }
+
+    pub fn parse(
+        input: &str,
+        compartments: usize,
+        group_size: usize,
+        priorities: &Priorities,
+    ) -> Result<Self, String> {
+        let mut rucksacks = Vec::new();
+        for (index, line) in input.split('\n').enumerate() {
+            let rucksack = Rucksack::parse(line, compartments, priorities)
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+            rucksacks.push(rucksack);
+        }
+
+        if group_size == 0 || !rucksacks.len().is_multiple_of(group_size) {
+            return Err(format!(
+                "Cannot split {} rucksacks into groups of {}",
+                rucksacks.len(),
+                group_size
+            ));
+        }
+
+        Ok(Self {
+            rucksacks,
+            group_size,
+        })
+    }
+
     pub fn load_from(path: &str) -> Option<Self> {
         let data = fs::read_to_string(path).ok()?;
-        let cargo: Vec<Rucksack> = data.split('\n').map(|r| Rucksack::from(r)).collect();
-        Some(Cargo(cargo))
+        Self::from(&data)
     }
 
     pub fn individuals_score(&self) -> Option<u32> {
-        let scores = self.0.iter().map(|i| i.score());
+        let scores = self.rucksacks.iter().map(|i| i.score());
         let mut score: u32 = 0;
         for s in scores {
             score += s?;
@@ -151,10 +768,10 @@ impl Cargo {
     }
 
     pub fn groups_score(&self) -> Option<u32> {
-        let scores = self.0.chunks(3).map(|c| {
-            let group: Vec<Items> = c.iter().map(|r| r.items.clone()).collect();
-            Group(group).score()
-        });
+        let scores = self
+            .rucksacks
+            .chunks(self.group_size)
+            .map(|c| lowest_priority(c.iter().fold(u128::MAX, |a, r| a & r.items.mask())));
 
         let mut score: u32 = 0;
         for s in scores {
@@ -162,4 +779,55 @@ impl Cargo {
         }
         Some(score)
     }
+
+    /// List misplaced items of every rucksack and badge candidates of every group.
+    /// Lines are counted from 1, items are ordered by priority.
+    /// ```
+    /// use aoc2022::{Cargo, Item};
+    ///
+    /// let cargo = Cargo::from("abca\nabbd\nxyzw\nabAb\naBaB\nCDCD").unwrap();
+    /// let report = cargo.diagnose();
+    ///
+    /// assert_eq!(report.rucksacks[0].line, 1);
+    /// assert_eq!(report.rucksacks[0].misplaced, vec![Item('a')]);
+    /// assert_eq!(report.rucksacks[2].misplaced, vec![]);
+    /// assert_eq!(report.rucksacks[5].misplaced, vec![Item('C'), Item('D')]);
+    /// assert_eq!(report.suspicious_rucksacks().len(), 3);
+    ///
+    /// assert_eq!(report.groups[0].lines, vec![1, 2, 3]);
+    /// assert_eq!(report.groups[0].candidates, vec![]);
+    /// assert_eq!(report.groups[1].candidates, vec![]);
+    ///
+    /// let cargo = Cargo::from("abca\nabbd\nbaxa").unwrap();
+    /// let report = cargo.diagnose();
+    /// assert_eq!(report.groups[0].candidates, vec![Item('a'), Item('b')]);
+    /// assert!(report.groups[0].is_suspicious());
+    /// ```
+    pub fn diagnose(&self) -> Diagnostics {
+        let rucksacks = self
+            .rucksacks
+            .iter()
+            .enumerate()
+            .map(|(index, r)| RucksackReport {
+                line: index + 1,
+                misplaced: r.misplaced(),
+            })
+            .collect();
+
+        let groups = self
+            .rucksacks
+            .chunks(self.group_size)
+            .enumerate()
+            .map(|(index, c)| {
+                let first = index * self.group_size + 1;
+                let mask = c.iter().fold(u128::MAX, |a, r| a & r.items.mask());
+                GroupReport {
+                    lines: (first..first + c.len()).collect(),
+                    candidates: Items::items_of(mask, c.iter().map(|r| &r.items)),
+                }
+            })
+            .collect();
+
+        Diagnostics { rucksacks, groups }
+    }
 }
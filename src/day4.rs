@@ -1,28 +1,305 @@
 use std::fs;
 
-pub struct Section {
+/// A closed range of sections
+/// ```
+/// use aoc2022::Interval;
+///
+/// let a = Interval::from("2-8").unwrap();
+/// let b = Interval::new(3, 9).unwrap();
+/// assert_eq!(a.len(), 7);
+/// assert!(a.overlaps(&b));
+/// assert!(!a.contains(&b));
+/// assert_eq!(a.intersection(&b), Interval::new(3, 8));
+///
+/// assert_eq!(Interval::new(5, 3), None);
+/// assert!(Interval::from("5-3").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
     pub min: u32,
     pub max: u32,
 }
-impl Section {
+impl Interval {
+    pub fn new(min: u32, max: u32) -> Option<Self> {
+        if min <= max {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // Parse the interval from a string like: "2-8"
     pub fn from(input: &str) -> Option<Self> {
         let mut data = input.split('-');
         let min: u32 = data.next()?.parse().ok()?;
         let max: u32 = data.next()?.parse().ok()?;
-        Some(Self { min, max })
+        Self::new(min, max)
+    }
+
+    // The number of sections in the interval
+    pub fn len(&self) -> u64 {
+        (self.max - self.min) as u64 + 1
+    }
+
+    // Intervals are never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min <= other.min && other.max <= self.max
+    }
+
+    pub fn contains_point(&self, section: u32) -> bool {
+        self.min <= section && section <= self.max
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+}
+
+// The assignment of an elf is an interval of sections
+pub type Section = Interval;
+
+/// A normalised set of sections: sorted intervals which neither overlap nor touch
+/// ```
+/// use aoc2022::{Interval, IntervalSet};
+///
+/// let i = |min, max| Interval::new(min, max).unwrap();
+///
+/// let a = IntervalSet::from(vec![i(5, 7), i(1, 3), i(4, 4), i(10, 12)]);
+/// assert_eq!(a.intervals(), &[i(1, 7), i(10, 12)]);
+/// assert_eq!(a.len(), 10);
+///
+/// let b = IntervalSet::from(vec![i(6, 11)]);
+/// assert_eq!(a.union(&b).intervals(), &[i(1, 12)]);
+/// assert_eq!(a.intersection(&b).intervals(), &[i(6, 7), i(10, 11)]);
+/// assert_eq!(a.difference(&b).intervals(), &[i(1, 5), i(12, 12)]);
+/// assert_eq!(a.complement(i(0, 15)).intervals(), &[i(0, 0), i(8, 9), i(13, 15)]);
+///
+/// assert!(a.contains_point(4));
+/// assert!(!a.contains_point(8));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet(Vec<Interval>);
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    // Normalise any list of intervals
+    pub fn from(mut intervals: Vec<Interval>) -> Self {
+        intervals.sort();
+        let mut output: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match output.last_mut() {
+                Some(last) if interval.min as u64 <= last.max as u64 + 1 => {
+                    last.max = last.max.max(interval.max);
+                }
+                _ => output.push(interval),
+            }
+        }
+        Self(output)
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.0
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        let mut intervals = std::mem::take(&mut self.0);
+        intervals.push(interval);
+        *self = Self::from(intervals);
+    }
+
+    // The total number of sections
+    pub fn len(&self) -> u64 {
+        self.0.iter().map(|i| i.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains_point(&self, section: u32) -> bool {
+        let index = self.0.partition_point(|i| i.max < section);
+        self.0.get(index).is_some_and(|i| i.contains_point(section))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from(self.0.iter().chain(other.0.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut output = Vec::new();
+        let (mut a, mut b) = (0, 0);
+        while a < self.0.len() && b < other.0.len() {
+            if let Some(i) = self.0[a].intersection(&other.0[b]) {
+                output.push(i);
+            }
+            if self.0[a].max < other.0[b].max {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+        Self(output)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        match self.bounds() {
+            Some(bounds) => self.intersection(&other.complement(bounds)),
+            None => Self::new(),
+        }
+    }
+
+    // Sections within the bounds which are not in the set
+    pub fn complement(&self, bounds: Interval) -> Self {
+        let mut output = Vec::new();
+        let mut next = bounds.min as u64;
+        for i in self.0.iter() {
+            if i.max < bounds.min || i.min > bounds.max {
+                continue;
+            }
+            if (i.min as u64) > next {
+                output.push(Interval {
+                    min: next as u32,
+                    max: i.min - 1,
+                });
+            }
+            next = i.max as u64 + 1;
+        }
+        if next <= bounds.max as u64 {
+            output.push(Interval {
+                min: next as u32,
+                max: bounds.max,
+            });
+        }
+        Self(output)
+    }
+
+    // The smallest interval covering the whole set
+    pub fn bounds(&self) -> Option<Interval> {
+        Interval::new(self.0.first()?.min, self.0.last()?.max)
+    }
+}
+
+/// A team of two or more elves with their assignments
+/// ```
+/// use aoc2022::Team;
+///
+/// let team = Team::from("2-4,3-5,4-8").unwrap();
+/// assert_eq!(team.members().len(), 3);
+/// assert_eq!(team.fully_contained(), false);
+/// assert_eq!(team.overlaps(), true);
+/// assert_eq!(team.count_overlapping_pairs(), 3);
+///
+/// let team = Team::from("2-4,5-7,3-3").unwrap();
+/// assert_eq!(team.fully_contained(), true);
+/// assert_eq!(team.overlaps(), false);
+/// assert_eq!(team.count_overlapping_pairs(), 1);
+///
+/// assert!(Team::from("2-4,").is_none());
+/// assert!(Team::from("2-4").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team(Vec<Section>);
+impl Team {
+    pub fn from(input: &str) -> Option<Self> {
+        let mut members = Vec::new();
+        for member in input.split(',') {
+            members.push(Section::from(member)?);
+        }
+        if members.len() < 2 {
+            return None;
+        }
+        Some(Self(members))
+    }
+
+    pub fn members(&self) -> &[Section] {
+        &self.0
+    }
+
+    /// Is one assignment fully contains another one
+    /// ```
+    /// use aoc2022::Team;
+    ///
+    /// assert_eq!(Team::from("2-8,3-7,9-9").unwrap().fully_contained(), true);
+    /// assert_eq!(Team::from("4-4,1-3,5-6").unwrap().fully_contained(), false);
+    /// assert_eq!(Team::from("1-3,2-4,3-5").unwrap().fully_contained(), false);
+    /// ```
+    pub fn fully_contained(&self) -> bool {
+        self.0.iter().enumerate().any(|(i, a)| {
+            self.0
+                .iter()
+                .enumerate()
+                .any(|(j, b)| i != j && a.contains(b))
+        })
+    }
+
+    /// If all assignments share some section
+    /// ```
+    /// use aoc2022::Team;
+    ///
+    /// assert_eq!(Team::from("1-5,3-7,5-9").unwrap().overlaps(), true);
+    /// assert_eq!(Team::from("1-3,2-4,3-5,3-3").unwrap().overlaps(), true);
+    /// assert_eq!(Team::from("1-2,2-3,3-4").unwrap().overlaps(), false);
+    /// ```
+    pub fn overlaps(&self) -> bool {
+        self.shared().is_some()
+    }
+
+    // Sections assigned to all members of the team
+    pub fn shared(&self) -> Option<Interval> {
+        let mut members = self.0.iter();
+        let first = *members.next()?;
+        members.try_fold(first, |a, m| a.intersection(m))
+    }
+
+    // The number of pairs of members with overlapping assignments
+    pub fn count_overlapping_pairs(&self) -> usize {
+        let mut output = 0;
+        for (i, a) in self.0.iter().enumerate() {
+            output += self.0[i + 1..].iter().filter(|b| a.overlaps(b)).count();
+        }
+        output
     }
 }
 
-pub struct Pair(Section, Section);
+/// The team of exactly two elves
+/// ```
+/// use aoc2022::Pair;
+///
+/// assert!(Pair::from("2-4,6-8").is_some());
+/// assert!(Pair::from("2-4").is_none());
+/// assert!(Pair::from("2-4,6-8,1-1").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair(Team);
 impl Pair {
     pub fn from(input: &str) -> Option<Self> {
-        let mut data = input.split(',');
-        let left = Section::from(data.next()?)?;
-        let right = Section::from(data.next()?)?;
-        Some(Self(left, right))
+        let team = Team::from(input)?;
+        if team.0.len() == 2 {
+            Some(Self(team))
+        } else {
+            None
+        }
+    }
+
+    pub fn team(&self) -> &Team {
+        &self.0
+    }
+
+    pub fn members(&self) -> &[Section] {
+        self.0.members()
     }
 
-    /// Is one assignment fully contains the other
+    /// Is one assignment fully contains the other one
     /// ```
     /// use aoc2022::Pair;
     ///
@@ -39,11 +316,10 @@ impl Pair {
     /// assert_eq!(pair.fully_contained(), true);
     /// ```
     pub fn fully_contained(&self) -> bool {
-        (self.0.min <= self.1.min) & (self.0.max >= self.1.max)
-            || (self.0.min >= self.1.min) & (self.0.max <= self.1.max)
+        self.0.fully_contained()
     }
 
-    /// If assignments overlap
+    /// If both assignments share some section
     /// ```
     /// use aoc2022::Pair;
     ///
@@ -63,19 +339,357 @@ impl Pair {
     /// assert_eq!(pair.overlaps(), false);
     /// ```
     pub fn overlaps(&self) -> bool {
-        (self.0.min <= self.1.max) & (self.1.min <= self.0.max)
+        self.0.overlaps()
+    }
+
+    pub fn shared(&self) -> Option<Interval> {
+        self.0.shared()
     }
+
+    pub fn count_overlapping_pairs(&self) -> usize {
+        self.0.count_overlapping_pairs()
+    }
+}
+
+/// The assignment of a single elf: the line (from 1) and the position in the line (from 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assignment {
+    pub line: usize,
+    pub member: usize,
+    pub sections: Interval,
 }
 
-pub struct Pairs(Vec<Pair>);
+/// The number of elves assigned to every section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    // Adjacent intervals with their counters, from the first assigned section to the last one
+    pub profile: Vec<(Interval, usize)>,
+    // Assignments whose every section is covered by someone else
+    pub redundant: Vec<Assignment>,
+}
+impl Coverage {
+    // Build the profile with a sweep line over the assignments
+    pub fn from(assignments: &[Assignment]) -> Self {
+        let mut events: Vec<(u64, i64)> = Vec::with_capacity(assignments.len() * 2);
+        for a in assignments {
+            events.push((a.sections.min as u64, 1));
+            events.push((a.sections.max as u64 + 1, -1));
+        }
+        events.sort();
+
+        let mut profile: Vec<(Interval, usize)> = Vec::new();
+        let mut count: i64 = 0;
+        let mut index = 0;
+        while index < events.len() {
+            let point = events[index].0;
+            while index < events.len() && events[index].0 == point {
+                count += events[index].1;
+                index += 1;
+            }
+            let next = match events.get(index) {
+                Some(&(next, _)) => next,
+                None => break,
+            };
+            match profile.last_mut() {
+                Some((last, c)) if *c == count as usize => last.max = (next - 1) as u32,
+                _ => profile.push((
+                    Interval {
+                        min: point as u32,
+                        max: (next - 1) as u32,
+                    },
+                    count as usize,
+                )),
+            }
+        }
+
+        let mut coverage = Self {
+            profile,
+            redundant: Vec::new(),
+        };
+        coverage.redundant = assignments
+            .iter()
+            .filter(|a| coverage.min_count(&a.sections) > 1)
+            .copied()
+            .collect();
+        coverage
+    }
+
+    pub fn count_at(&self, section: u32) -> usize {
+        let index = self.profile.partition_point(|(i, _)| i.max < section);
+        match self.profile.get(index) {
+            Some((i, c)) if i.contains_point(section) => *c,
+            _ => 0,
+        }
+    }
+
+    // Sections between the first and the last assigned ones with no elf
+    pub fn unassigned(&self) -> IntervalSet {
+        self.select(|c| c == 0)
+    }
+
+    pub fn covered_by_more_than(&self, count: usize) -> IntervalSet {
+        self.select(|c| c > count)
+    }
+
+    // The maximum number of elves per section, and where it is reached
+    pub fn peak(&self) -> Option<(usize, IntervalSet)> {
+        let max = self.profile.iter().map(|&(_, c)| c).max()?;
+        Some((max, self.select(|c| c == max)))
+    }
+
+    fn select<F: Fn(usize) -> bool>(&self, filter: F) -> IntervalSet {
+        IntervalSet::from(
+            self.profile
+                .iter()
+                .filter(|&&(_, c)| filter(c))
+                .map(|&(i, _)| i)
+                .collect(),
+        )
+    }
+
+    // The smallest counter within the interval
+    fn min_count(&self, interval: &Interval) -> usize {
+        let start = self.profile.partition_point(|(i, _)| i.max < interval.min);
+        self.profile[start..]
+            .iter()
+            .take_while(|(i, _)| i.min <= interval.max)
+            .map(|&(_, c)| c)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// The static interval tree over assignments.
+/// Assignments are sorted by their first section, and every node of the implicit
+/// balanced tree keeps the last section covered by its subtree.
+#[derive(Debug, Clone)]
+pub struct IntervalTree {
+    items: Vec<Assignment>,
+    max: Vec<u32>,
+}
+impl IntervalTree {
+    pub fn from(mut items: Vec<Assignment>) -> Self {
+        items.sort_by_key(|a| (a.sections.min, a.sections.max, a.line, a.member));
+        let mut max = vec![0; items.len()];
+        Self::build(&items, &mut max, 0, items.len());
+        Self { items, max }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Assignments containing the section
+    pub fn at(&self, section: u32) -> Vec<Assignment> {
+        self.overlapping(Interval {
+            min: section,
+            max: section,
+        })
+    }
+
+    // Assignments overlapping the range, in the order of lines
+    pub fn overlapping(&self, range: Interval) -> Vec<Assignment> {
+        let mut output = Vec::new();
+        self.search(&range, 0, self.items.len(), &mut output);
+        output.sort_by_key(|a| (a.line, a.member));
+        output
+    }
+
+    // Fill the maximum of the subtree [lo, hi) rooted in the middle
+    fn build(items: &[Assignment], max: &mut [u32], lo: usize, hi: usize) -> u32 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let left = Self::build(items, max, lo, mid);
+        let right = Self::build(items, max, mid + 1, hi);
+        max[mid] = items[mid].sections.max.max(left).max(right);
+        max[mid]
+    }
+
+    fn search(&self, range: &Interval, lo: usize, hi: usize, output: &mut Vec<Assignment>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max[mid] < range.min {
+            return;
+        }
+        self.search(range, lo, mid, output);
+        let item = self.items[mid];
+        if item.sections.min > range.max {
+            return;
+        }
+        if item.sections.overlaps(range) {
+            output.push(item);
+        }
+        self.search(range, mid + 1, hi, output);
+    }
+}
+
+/// Teams listed line by line
+/// ```
+/// use aoc2022::Teams;
+///
+/// let teams = Teams::from("2-4,6-8,3-3\n1-9,2-3,3-5").unwrap();
+/// assert_eq!(teams.count_fully_contained(), 2);
+/// assert_eq!(teams.count_overlapped(), 1);
+/// assert_eq!(teams.count_overlapping_pairs(), 4);
+/// assert_eq!(teams.assignments().len(), 6);
+/// ```
+pub struct Teams(Vec<Team>);
+impl Teams {
+    pub fn from(input: &str) -> Option<Self> {
+        let mut teams: Vec<Team> = Vec::new();
+        for line in input.split('\n') {
+            teams.push(Team::from(line)?)
+        }
+        Some(Self(teams))
+    }
+
+    pub fn teams(&self) -> &[Team] {
+        &self.0
+    }
+
+    pub fn load_from(path: &str) -> Option<Self> {
+        let data = fs::read_to_string(path).ok()?;
+        Self::from(&data)
+    }
+
+    /// Count teams where one assignment fully contains another one
+    /// ```
+    /// use aoc2022::Teams;
+    ///
+    /// let teams = Teams::from("2-8,3-7,9-9\n1-3,2-4,3-5\n1-2,2-3,3-4").unwrap();
+    /// assert_eq!(teams.count_fully_contained(), 1);
+    /// ```
+    pub fn count_fully_contained(&self) -> u32 {
+        self.0
+            .iter()
+            .fold(0, |a, team| a + if team.fully_contained() { 1 } else { 0 })
+    }
+
+    /// Count teams where all assignments share some section
+    /// ```
+    /// use aoc2022::Teams;
+    ///
+    /// let teams = Teams::from("2-8,3-7,9-9\n1-3,2-4,3-5\n1-2,2-3,3-4").unwrap();
+    /// assert_eq!(teams.count_overlapped(), 1);
+    /// ```
+    pub fn count_overlapped(&self) -> u32 {
+        self.0
+            .iter()
+            .fold(0, |a, team| a + if team.overlaps() { 1 } else { 0 })
+    }
+
+    pub fn count_overlapping_pairs(&self) -> usize {
+        self.0
+            .iter()
+            .map(|team| team.count_overlapping_pairs())
+            .sum()
+    }
+
+    /// Sections assigned to all elves of some team
+    /// ```
+    /// use aoc2022::{Interval, Teams};
+    ///
+    /// let teams = Teams::from("2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8").unwrap();
+    /// let i = |min, max| Interval::new(min, max).unwrap();
+    /// assert_eq!(teams.shared_sections().intervals(), &[i(3, 7)]);
+    /// ```
+    pub fn shared_sections(&self) -> IntervalSet {
+        IntervalSet::from(self.0.iter().filter_map(|team| team.shared()).collect())
+    }
+
+    // Assignments of all elves in the order of lines
+    pub fn assignments(&self) -> Vec<Assignment> {
+        let mut output = Vec::with_capacity(self.0.len() * 2);
+        for (index, team) in self.0.iter().enumerate() {
+            for (member, &sections) in team.0.iter().enumerate() {
+                output.push(Assignment {
+                    line: index + 1,
+                    member,
+                    sections,
+                });
+            }
+        }
+        output
+    }
+
+    /// Analyse all assignments at once
+    /// ```
+    /// use aoc2022::{Assignment, Interval, Teams};
+    ///
+    /// let i = |min, max| Interval::new(min, max).unwrap();
+    ///
+    /// let teams = Teams::from("1-2,5-6\n5-5,6-8").unwrap();
+    /// let coverage = teams.coverage();
+    /// assert_eq!(coverage.profile, vec![(i(1, 2), 1), (i(3, 4), 0), (i(5, 6), 2), (i(7, 8), 1)]);
+    /// assert_eq!(coverage.unassigned().intervals(), &[i(3, 4)]);
+    /// assert_eq!(coverage.count_at(6), 2);
+    /// assert_eq!(coverage.count_at(9), 0);
+    /// assert_eq!(coverage.redundant, vec![
+    ///     Assignment { line: 1, member: 1, sections: i(5, 6) },
+    ///     Assignment { line: 2, member: 0, sections: i(5, 5) },
+    /// ]);
+    ///
+    /// let teams = Teams::from("2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8").unwrap();
+    /// let coverage = teams.coverage();
+    /// let (peak, sections) = coverage.peak().unwrap();
+    /// assert_eq!(peak, 8);
+    /// assert_eq!(sections.intervals(), &[i(6, 6)]);
+    /// assert_eq!(coverage.covered_by_more_than(6).intervals(), &[i(4, 6)]);
+    /// assert!(coverage.unassigned().is_empty());
+    /// assert_eq!(coverage.redundant.len(), 11);
+    /// ```
+    pub fn coverage(&self) -> Coverage {
+        Coverage::from(&self.assignments())
+    }
+
+    /// Index assignments for stabbing and range queries
+    /// ```
+    /// use aoc2022::{Assignment, Interval, Teams};
+    ///
+    /// let i = |min, max| Interval::new(min, max).unwrap();
+    ///
+    /// let teams = Teams::from("2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8").unwrap();
+    /// let tree = teams.index();
+    /// assert_eq!(tree.len(), 12);
+    ///
+    /// assert_eq!(tree.at(9), vec![Assignment { line: 3, member: 1, sections: i(7, 9) }]);
+    /// assert_eq!(tree.at(1), vec![]);
+    ///
+    /// let found: Vec<(usize, usize)> = tree.at(3).iter().map(|a| (a.line, a.member)).collect();
+    /// assert_eq!(found, vec![(1, 0), (2, 0), (4, 0), (4, 1), (6, 0)]);
+    ///
+    /// let found: Vec<(usize, usize)> = tree.overlapping(i(8, 20)).iter().map(|a| (a.line, a.member)).collect();
+    /// assert_eq!(found, vec![(1, 1), (3, 1), (4, 0), (6, 1)]);
+    /// ```
+    pub fn index(&self) -> IntervalTree {
+        IntervalTree::from(self.assignments())
+    }
+}
+
+/// Teams of exactly two elves
+/// ```
+/// use aoc2022::Pairs;
+///
+/// assert!(Pairs::from("2-4,6-8\n2-3,4-5").is_some());
+/// assert!(Pairs::from("2-4,6-8\n2-3,4-5,6-7").is_none());
+/// ```
+pub struct Pairs(Teams);
 impl Pairs {
     pub fn from(input: &str) -> Option<Self> {
-        let list = input.split('\n').map(|s| Pair::from(s));
-        let mut pairs: Vec<Pair> = Vec::new();
-        for pair in list {
-            pairs.push(pair?)
+        let teams = Teams::from(input)?;
+        if teams.0.iter().all(|team| team.0.len() == 2) {
+            Some(Self(teams))
+        } else {
+            None
         }
-        Some(Self(pairs))
     }
 
     pub fn load_from(path: &str) -> Option<Self> {
@@ -83,6 +697,10 @@ impl Pairs {
         Self::from(&data)
     }
 
+    pub fn teams(&self) -> &[Team] {
+        self.0.teams()
+    }
+
     /// Count inclusive pairs where one assignment fully contains the other
     /// ```
     /// use aoc2022::Pairs;
@@ -91,12 +709,10 @@ impl Pairs {
     /// assert_eq!(pairs.count_fully_contained(), 2);
     /// ```
     pub fn count_fully_contained(&self) -> u32 {
-        self.0
-            .iter()
-            .fold(0, |a, pair| a + if pair.fully_contained() { 1 } else { 0 })
+        self.0.count_fully_contained()
     }
 
-    /// Count inclusive pairs where one assignment fully contains the other
+    /// Count pairs where assignments overlap
     /// ```
     /// use aoc2022::Pairs;
     ///
@@ -104,8 +720,26 @@ impl Pairs {
     /// assert_eq!(pairs.count_overlapped(), 4);
     /// ```
     pub fn count_overlapped(&self) -> u32 {
-        self.0
-            .iter()
-            .fold(0, |a, pair| a + if pair.overlaps() { 1 } else { 0 })
+        self.0.count_overlapped()
+    }
+
+    pub fn count_overlapping_pairs(&self) -> usize {
+        self.0.count_overlapping_pairs()
+    }
+
+    pub fn shared_sections(&self) -> IntervalSet {
+        self.0.shared_sections()
+    }
+
+    pub fn assignments(&self) -> Vec<Assignment> {
+        self.0.assignments()
+    }
+
+    pub fn coverage(&self) -> Coverage {
+        self.0.coverage()
+    }
+
+    pub fn index(&self) -> IntervalTree {
+        self.0.index()
     }
 }
@@ -1,8 +1,10 @@
 use regex::Regex;
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fs;
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Stack(VecDeque<char>);
 impl Stack {
     pub fn new() -> Self {
@@ -24,40 +26,109 @@ impl Stack {
     }
 }
 
+/// Stacks of crates labelled by numbers or words
+/// ```
+/// use aoc2022::Content;
+///
+/// let content = Content::from("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ").unwrap();
+/// assert_eq!(content.labels(), &["1", "2", "3"]);
+/// assert_eq!(content.top().unwrap(), "NDP");
+///
+/// let header = [
+///     "[A]                                         [B]",
+///     "[C]                 [D]             [E] [F]",
+///     " 1   2   3   4   5   6   7   8   9   10  11  12 ",
+/// ].join("\n");
+/// let content = Content::from(&header).unwrap();
+/// assert_eq!(content.labels().len(), 12);
+/// assert_eq!(content.top().unwrap(), "A    D   EFB");
+///
+/// let content = Content::from("[A]    [B]\nleft  right").unwrap();
+/// assert_eq!(content.labels(), &["left", "right"]);
+///
+/// let error = Content::parse("[A]    [B]\n 1   2").unwrap_err();
+/// assert_eq!(error, "Crate [B] at column 8 is not under any stack label");
+/// ```
 #[derive(Debug, Clone)]
 pub struct Content {
-    stacks: HashMap<char, Stack>,
-    places: HashMap<usize, char>,
-    labels: Vec<char>,
+    stacks: HashMap<String, Stack>,
+    labels: Vec<String>,
+    // columns of labels in the drawing
+    columns: Vec<(usize, usize)>,
 }
 impl Content {
-    // Init stack from line of numbers like: " 1   2   3 "
-    fn init_from(line: &str) -> Self {
+    // Init stack from line of labels like: " 1   2   3 "
+    fn init_from(line: &str) -> Result<Self, String> {
         let mut stacks = HashMap::new();
-        let mut places = HashMap::new();
         let mut labels = Vec::new();
-        for (p, c) in line.chars().enumerate() {
-            if c != ' ' {
-                stacks.insert(c, Stack::new());
-                places.insert(p, c);
-                labels.push(c);
+        let mut columns = Vec::new();
+        for (start, end, label) in Self::words(line) {
+            if stacks.insert(label.clone(), Stack::new()).is_some() {
+                return Err(format!("Duplicated stack label {:?}", label));
             }
+            labels.push(label);
+            columns.push((start, end));
+        }
+        if labels.is_empty() {
+            return Err("No stack labels found".into());
         }
-        Self {
+        Ok(Self {
             stacks,
-            places,
             labels,
-        }
+            columns,
+        })
     }
 
     // Fill stack from line like: "[A] [B] [C]" or "[D]     [E]"
-    fn add_tier(&mut self, line: &str) -> Option<()> {
-        for (index, item) in line.chars().enumerate() {
-            if let Some(c) = self.places.get(&index) {
-                self.stacks.get_mut(c)?.push(item);
+    fn add_tier(&mut self, line: &str) -> Result<(), String> {
+        for (start, end, word) in Self::words(line) {
+            let mut chars = word.chars();
+            let item = match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                (Some('['), Some(item), Some(']'), None) => item,
+                _ => {
+                    return Err(format!(
+                        "Cannot parse crate {} at column {}",
+                        word,
+                        start + 1
+                    ))
+                }
+            };
+            let index = self
+                .columns
+                .iter()
+                .position(|&(s, e)| s <= end && start <= e)
+                .ok_or(format!(
+                    "Crate {} at column {} is not under any stack label",
+                    word,
+                    start + 1
+                ))?;
+            let label = &self.labels[index];
+            self.stacks
+                .get_mut(label)
+                .ok_or(format!("Unknown stack {:?}", label))?
+                .push(item);
+        }
+        Ok(())
+    }
+
+    // Words of the line along with their first and last columns
+    fn words(line: &str) -> Vec<(usize, usize, String)> {
+        let mut output = Vec::new();
+        let mut word = String::new();
+        let mut start = 0;
+        for (index, c) in line.chars().chain([' ']).enumerate() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    output.push((start, index - 1, std::mem::take(&mut word)));
+                }
+            } else {
+                if word.is_empty() {
+                    start = index;
+                }
+                word.push(c);
             }
         }
-        Some(())
+        output
     }
 
     // Prepare content from input file header like:
@@ -65,24 +136,143 @@ impl Content {
     // [A] [B] [C]
     //  1   2   3
     pub fn from(header: &str) -> Option<Self> {
+        Self::parse(header).ok()
+    }
+
+    pub fn parse(header: &str) -> Result<Self, String> {
         let mut lines: Vec<&str> = header.split('\n').collect();
         lines.reverse();
         let mut lines = lines.into_iter();
-        let mut content = Self::init_from(lines.next()?);
+        let mut content = Self::init_from(lines.next().ok_or("Empty drawing")?)?;
         for line in lines {
             content.add_tier(line)?;
         }
-        Some(content)
+        Ok(content)
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
     }
 
     // Push the item to the corresponding stack
-    pub fn push(&mut self, stack: char, item: char) -> Option<()> {
-        Some(self.stacks.get_mut(&stack)?.push(item))
+    pub fn push(&mut self, stack: &str, item: char) -> Option<()> {
+        self.stacks.get_mut(stack)?.push(item);
+        Some(())
     }
 
     // Pop the item from the corresponding stack
-    pub fn pop(&mut self, stack: char) -> Option<char> {
-        self.stacks.get_mut(&stack)?.pop()
+    pub fn pop(&mut self, stack: &str) -> Option<char> {
+        self.stacks.get_mut(stack)?.pop()
+    }
+
+    // Take crates from the top of the stack, the topmost goes first
+    pub fn lift(&mut self, stack: &str, count: usize) -> Result<Vec<char>, String> {
+        let items = &mut self
+            .stacks
+            .get_mut(stack)
+            .ok_or(format!("refers to unknown stack {}", stack))?
+            .0;
+        if items.len() < count {
+            return Err(if items.is_empty() {
+                format!("pops from empty stack {}", stack)
+            } else {
+                format!(
+                    "pops {} crates from stack {} of {}",
+                    count,
+                    stack,
+                    items.len()
+                )
+            });
+        }
+        Ok(items.drain(..count).collect())
+    }
+
+    // Put crates onto the stack one by one
+    pub fn lower<I: IntoIterator<Item = char>>(
+        &mut self,
+        stack: &str,
+        items: I,
+    ) -> Result<(), String> {
+        let target = self
+            .stacks
+            .get_mut(stack)
+            .ok_or(format!("refers to unknown stack {}", stack))?;
+        for item in items {
+            target.push(item);
+        }
+        Ok(())
+    }
+
+    /// Draw the content in the same format it is parsed from.
+    /// Every column is wide enough for its label surrounded by spaces.
+    /// ```
+    /// use aoc2022::Content;
+    ///
+    /// let header = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+    /// let content = Content::from(header).unwrap();
+    /// assert_eq!(content.render(), header);
+    ///
+    /// let header = [
+    ///     "[A]                                          ",
+    ///     "[B]             [C]                 [D]      ",
+    ///     " 1   2   3   4   5   6   7   8   9   10   11 ",
+    /// ].join("\n");
+    /// let content = Content::from(&header).unwrap();
+    /// assert_eq!(content.render(), header);
+    ///
+    /// let content = Content::from("    [A]\n[B] [C]\nleft right").unwrap();
+    /// assert_eq!(content.render(), "       [A]    \n[B]    [C]    \n left   right ");
+    /// assert_eq!(Content::from(&content.render()).unwrap(), content);
+    /// ```
+    pub fn render(&self) -> String {
+        let widths: Vec<usize> = self
+            .labels
+            .iter()
+            .map(|l| (l.chars().count() + 2).max(3))
+            .collect();
+        let heights: Vec<usize> = self.labels.iter().map(|l| self.stacks[l].0.len()).collect();
+        let height = heights.iter().copied().max().unwrap_or(0);
+
+        let mut lines = Vec::with_capacity(height + 1);
+        for row in 0..height {
+            let mut cells = Vec::with_capacity(self.labels.len());
+            for (index, label) in self.labels.iter().enumerate() {
+                let cell = match (row + heights[index]).checked_sub(height) {
+                    Some(depth) => format!("[{}]", self.stacks[label].0[depth]),
+                    None => String::new(),
+                };
+                cells.push(format!("{:width$}", cell, width = widths[index]));
+            }
+            lines.push(cells.join(" "));
+        }
+
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .zip(widths.iter())
+            .map(|(label, &width)| format!(" {:width$}", label, width = width - 1))
+            .collect();
+        lines.push(labels.join(" "));
+        lines.join("\n")
+    }
+
+    // Crates of every stack from the bottom up
+    fn state(&self) -> State {
+        self.labels
+            .iter()
+            .map(|l| self.stacks[l].0.iter().rev().copied().collect())
+            .collect()
+    }
+
+    // The same stacks filled with other crates
+    fn with_state(&self, state: &State) -> Self {
+        let mut content = self.clone();
+        for (label, items) in self.labels.iter().zip(state) {
+            content
+                .stacks
+                .insert(label.clone(), Stack(items.iter().rev().copied().collect()));
+        }
+        content
     }
 
     // Get the top tier of items
@@ -95,40 +285,152 @@ impl Content {
     }
 }
 
-#[derive(Debug)]
-pub struct Command(i32, char, char);
+// Contents are equal when they have the same stacks, however they are drawn
+impl PartialEq for Content {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels == other.labels && self.stacks == other.stacks
+    }
+}
+impl Eq for Content {}
+
+/// Move crates between stacks
+/// ```
+/// use aoc2022::Command;
+///
+/// let command = Command::from("move 12 from 10 to left").unwrap();
+/// assert_eq!(command.count, 12);
+/// assert_eq!(command.from, "10");
+/// assert_eq!(command.to, "left");
+///
+/// assert!(Command::from("move 1 from 2").is_none());
+/// assert_eq!(
+///     Command::parse("lift 1 from 2 to 3").unwrap_err(),
+///     "Cannot parse command \"lift 1 from 2 to 3\""
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub count: usize,
+    pub from: String,
+    pub to: String,
+}
 impl Command {
     // parse command from line like: move 1 from 2 to 1
     pub fn from(line: &str) -> Option<Self> {
-        let mut output = Self(0, ' ', ' ');
-        for cap in Regex::new(r"move (\d+) from (\d) to (\d)")
-            .ok()?
-            .captures_iter(line)
-        {
-            output.0 = cap[1].parse::<i32>().ok()?;
-            output.1 = cap[2].chars().next()?;
-            output.2 = cap[3].chars().next()?;
-        }
-        Some(output)
+        Self::parse(line).ok()
+    }
+
+    pub fn parse(line: &str) -> Result<Self, String> {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        let pattern = PATTERN.get_or_init(|| {
+            Regex::new(r"^move (\d+) from (\S+) to (\S+)$").expect("valid command pattern")
+        });
+        let error = || format!("Cannot parse command {:?}", line);
+        let cap = pattern.captures(line.trim_end()).ok_or_else(error)?;
+        Ok(Self {
+            count: cap[1].parse().map_err(|_| error())?,
+            from: cap[2].into(),
+            to: cap[3].into(),
+        })
+    }
+
+    // Write the command back to the line like: move 1 from 2 to 1
+    pub fn render(&self) -> String {
+        format!("move {} from {} to {}", self.count, self.from, self.to)
     }
 
     pub fn apply_old(&self, content: &mut Content) -> Option<()> {
-        for _ in 0..self.0 {
-            let item = { content.pop(self.1)? };
-            content.push(self.2, item)?
-        }
-        Some(())
+        CrateMover9000.apply(self, content).ok()
     }
 
     pub fn apply_new(&self, content: &mut Content) -> Option<()> {
-        let mut buffer: Vec<char> = Vec::new();
-        for _ in 0..self.0 {
-            buffer.push(content.pop(self.1)?);
+        CrateMover9001.apply(self, content).ok()
+    }
+}
+
+/// The way a crane executes commands
+pub trait CraneModel {
+    fn apply(&self, command: &Command, content: &mut Content) -> Result<(), String>;
+}
+
+/// Moves crates one at a time
+pub struct CrateMover9000;
+impl CraneModel for CrateMover9000 {
+    fn apply(&self, command: &Command, content: &mut Content) -> Result<(), String> {
+        let items = content.lift(&command.from, command.count)?;
+        content.lower(&command.to, items)
+    }
+}
+
+/// Moves all crates at once keeping their order
+pub struct CrateMover9001;
+impl CraneModel for CrateMover9001 {
+    fn apply(&self, command: &Command, content: &mut Content) -> Result<(), String> {
+        let items = content.lift(&command.from, command.count)?;
+        content.lower(&command.to, items.into_iter().rev())
+    }
+}
+
+/// Moves at most `capacity` crates at once keeping their order,
+/// so larger moves are split into several lifts
+/// ```
+/// use aoc2022::{Crane, LimitedCrane};
+///
+/// let crane = Crane::from("[A]\n[B]\n[C]\n[D]\n[E]\n 1   2 \n\nmove 5 from 1 to 2").unwrap();
+/// assert_eq!(crane.run(&LimitedCrane(2)).unwrap(), " E");
+/// assert_eq!(crane.run(&LimitedCrane(5)).unwrap(), " A");
+/// assert!(crane.run(&LimitedCrane(0)).is_err());
+/// ```
+pub struct LimitedCrane(pub usize);
+impl CraneModel for LimitedCrane {
+    fn apply(&self, command: &Command, content: &mut Content) -> Result<(), String> {
+        if self.0 == 0 && command.count > 0 {
+            return Err("cannot lift anything".into());
         }
-        for _ in 0..self.0 {
-            content.push(self.2, buffer.pop()?)?;
+        let mut left = command.count;
+        while left > 0 {
+            let size = left.min(self.0);
+            let items = content.lift(&command.from, size)?;
+            content.lower(&command.to, items.into_iter().rev())?;
+            left -= size;
         }
-        Some(())
+        Ok(())
+    }
+}
+
+/// Moves all crates at once like the CrateMover 9001,
+/// but turns over every other crate (from the second topmost one),
+/// so that its letter is read in the other case
+/// ```
+/// use aoc2022::{Crane, FlippingCrane};
+///
+/// let crane = Crane::from("[A]\n[B]\n[C]\n 1   2 \n\nmove 3 from 1 to 2").unwrap();
+/// assert_eq!(crane.run(&FlippingCrane).unwrap(), " A");
+///
+/// let crane = Crane::from("[A]\n[B]\n[C]\n 1   2 \n\nmove 2 from 1 to 2").unwrap();
+/// assert_eq!(crane.run(&FlippingCrane).unwrap(), "CA");
+///
+/// let crane = Crane::from("[A]\n[B]\n[C]\n 1   2 \n\nmove 2 from 1 to 2\nmove 2 from 2 to 1").unwrap();
+/// assert_eq!(crane.run(&FlippingCrane).unwrap(), "A ");
+/// ```
+pub struct FlippingCrane;
+impl CraneModel for FlippingCrane {
+    fn apply(&self, command: &Command, content: &mut Content) -> Result<(), String> {
+        let items: Vec<char> = content
+            .lift(&command.from, command.count)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| if index % 2 == 1 { flip(item) } else { item })
+            .collect();
+        content.lower(&command.to, items.into_iter().rev())
+    }
+}
+
+fn flip(item: char) -> char {
+    if item.is_lowercase() {
+        item.to_uppercase().next().unwrap_or(item)
+    } else {
+        item.to_lowercase().next().unwrap_or(item)
     }
 }
 
@@ -141,7 +443,25 @@ impl Command {
 /// let crane = Crane::from(data).unwrap();
 /// assert_eq!(crane.apply_old().unwrap(), "CMZ");
 /// assert_eq!(crane.apply_new().unwrap(), "MCD");
+///
+/// let data = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+/// move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 4";
+/// assert_eq!(Crane::parse(data).unwrap_err(), "Line 8: Unknown stack \"4\"");
+///
+/// let data = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+/// move 1 from 2 to 1\nmove three from 1 to 3";
+/// assert_eq!(
+///     Crane::parse(data).unwrap_err(),
+///     "Line 7: Cannot parse command \"move three from 1 to 3\""
+/// );
+///
+/// // blank lines between commands are skipped, but the rest is still read
+/// let data = "[A]    \n 1   2 \n\nmove 1 from 1 to 2\n\nmove 1 from 2 to 1\nbogus line";
+/// assert_eq!(Crane::parse(data).unwrap_err(), "Line 7: Cannot parse command \"bogus line\"");
+/// let data = "[A]    \n 1   2 \n\nmove 1 from 1 to 2\n\nmove 1 from 2 to 1\n";
+/// assert_eq!(Crane::parse(data).unwrap().commands().len(), 2);
 /// ```
+#[derive(Debug)]
 pub struct Crane {
     content: Content,
     commands: Vec<Command>,
@@ -153,30 +473,434 @@ impl Crane {
     }
 
     pub fn from(data: &str) -> Option<Self> {
-        let mut parts = data.split("\n\n");
-        let content = Content::from(parts.next()?)?;
+        Self::parse(data).ok()
+    }
 
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let (header, rest) = data.split_once("\n\n").ok_or("No commands found")?;
+        let content = Content::parse(header)?;
+
+        let offset = header.split('\n').count() + 1;
         let mut commands: Vec<Command> = Vec::new();
-        for p in parts.next()?.split('\n') {
-            commands.push(Command::from(p)?)
+        let lines = rest.split('\n');
+        for (index, line) in lines.enumerate() {
+            let error = |e: String| format!("Line {}: {}", offset + index + 1, e);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let command = Command::parse(line).map_err(error)?;
+            for label in [&command.from, &command.to] {
+                if !content.stacks.contains_key(label) {
+                    return Err(error(format!("Unknown stack {:?}", label)));
+                }
+            }
+            commands.push(command)
         }
 
-        Some(Self { content, commands })
+        Ok(Self { content, commands })
     }
 
     pub fn apply_old(&self) -> Option<String> {
-        let mut content = self.content.clone();
-        for c in self.commands.iter() {
-            c.apply_old(&mut content)?;
-        }
-        content.top()
+        self.run(&CrateMover9000).ok()
     }
 
     pub fn apply_new(&self) -> Option<String> {
+        self.run(&CrateMover9001).ok()
+    }
+
+    // Apply all commands with the given crane model, commands are counted from 1
+    pub fn run(&self, model: &dyn CraneModel) -> Result<String, String> {
         let mut content = self.content.clone();
-        for c in self.commands.iter() {
-            c.apply_new(&mut content)?;
+        for (index, c) in self.commands.iter().enumerate() {
+            Self::apply(model, index, c, &mut content)?;
+        }
+        content.top().ok_or("Cannot read the top tier".into())
+    }
+
+    pub fn content(&self) -> &Content {
+        &self.content
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Write the whole input file back
+    /// ```
+    /// use aoc2022::Crane;
+    ///
+    /// let data = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+    /// move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2";
+    /// let crane = Crane::from(data).unwrap();
+    /// assert_eq!(crane.render(), data);
+    ///
+    /// let data = std::fs::read_to_string("data/5.in").unwrap();
+    /// assert_eq!(Crane::from(&data).unwrap().render(), data);
+    /// ```
+    pub fn render(&self) -> String {
+        let commands: Vec<String> = self.commands.iter().map(|c| c.render()).collect();
+        format!("{}\n\n{}", self.content.render(), commands.join("\n"))
+    }
+
+    /// The content after every command, up to the first failure
+    /// ```
+    /// use aoc2022::{Crane, CrateMover9000};
+    ///
+    /// let data = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+    /// move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 3 from 1 to 2";
+    /// let crane = Crane::from(data).unwrap();
+    ///
+    /// let tops: Vec<Result<String, String>> = crane
+    ///     .trace(&CrateMover9000)
+    ///     .map(|step| step.map(|content| content.top().unwrap()))
+    ///     .collect();
+    /// assert_eq!(tops, vec![
+    ///     Ok("DCP".into()),
+    ///     Ok(" CZ".into()),
+    ///     Ok("M Z".into()),
+    ///     Err("command 4 pops 3 crates from stack 1 of 2".into()),
+    /// ]);
+    /// ```
+    pub fn trace<'a>(&'a self, model: &'a dyn CraneModel) -> Trace<'a> {
+        Trace {
+            crane: self,
+            model,
+            content: self.content.clone(),
+            index: 0,
+            failed: false,
+        }
+    }
+
+    /// Walk through the commands back and forth
+    /// ```
+    /// use aoc2022::{Crane, CrateMover9001};
+    ///
+    /// let data = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+    /// move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 2 to 2";
+    /// let crane = Crane::from(data).unwrap();
+    /// let mut cursor = crane.cursor(&CrateMover9001);
+    ///
+    /// assert_eq!(cursor.position(), 0);
+    /// assert!(cursor.undo().is_none());
+    ///
+    /// assert_eq!(cursor.redo().unwrap().top().unwrap(), "DCP");
+    /// assert_eq!(cursor.redo().unwrap().top().unwrap(), " CD");
+    /// assert_eq!(cursor.undo().unwrap().top().unwrap(), "DCP");
+    /// assert_eq!(cursor.redo().unwrap().top().unwrap(), " CD");
+    /// assert_eq!(cursor.redo().unwrap().top().unwrap(), "C D");
+    /// assert_eq!(cursor.position(), 3);
+    /// assert_eq!(cursor.redo().unwrap_err(), "command 4 pops from empty stack 2");
+    /// assert_eq!(cursor.position(), 3);
+    ///
+    /// cursor.rewind();
+    /// assert_eq!(cursor.content(), crane.content());
+    /// ```
+    pub fn cursor<'a>(&'a self, model: &'a dyn CraneModel) -> Cursor<'a> {
+        Cursor {
+            crane: self,
+            model,
+            states: vec![self.content.clone()],
+            position: 0,
+        }
+    }
+
+    fn apply(
+        model: &dyn CraneModel,
+        index: usize,
+        command: &Command,
+        content: &mut Content,
+    ) -> Result<(), String> {
+        model
+            .apply(command, content)
+            .map_err(|e| format!("command {} {}", index + 1, e))
+    }
+}
+
+/// The iterator over contents after every command
+pub struct Trace<'a> {
+    crane: &'a Crane,
+    model: &'a dyn CraneModel,
+    content: Content,
+    index: usize,
+    failed: bool,
+}
+impl Iterator for Trace<'_> {
+    type Item = Result<Content, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let command = self.crane.commands.get(self.index)?;
+        let result = Crane::apply(self.model, self.index, command, &mut self.content);
+        self.index += 1;
+        match result {
+            Ok(()) => Some(Ok(self.content.clone())),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// The undo/redo cursor over the list of commands
+pub struct Cursor<'a> {
+    crane: &'a Crane,
+    model: &'a dyn CraneModel,
+    // contents after every applied command, starting from the initial one
+    states: Vec<Content>,
+    position: usize,
+}
+impl Cursor<'_> {
+    // The number of applied commands
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn content(&self) -> &Content {
+        &self.states[self.position]
+    }
+
+    // The command to be applied next
+    pub fn next_command(&self) -> Option<&Command> {
+        self.crane.commands.get(self.position)
+    }
+
+    // Step back by one command
+    pub fn undo(&mut self) -> Option<&Content> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.content())
+    }
+
+    // Apply the next command
+    pub fn redo(&mut self) -> Result<&Content, String> {
+        if self.position + 1 < self.states.len() {
+            self.position += 1;
+            return Ok(self.content());
+        }
+
+        let command = self
+            .next_command()
+            .ok_or(format!("No command after {}", self.position))?;
+        let mut content = self.content().clone();
+        Crane::apply(self.model, self.position, command, &mut content)?;
+        self.states.push(content);
+        self.position += 1;
+        Ok(self.content())
+    }
+
+    // Go back to the initial content
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+}
+
+/// A short list of commands turning one content into another
+/// ```
+/// use aoc2022::{Content, CrateMover9000, CrateMover9001, Plan};
+///
+/// let start = Content::from("[A]        \n[B]        \n[C]        \n 1   2   3 ").unwrap();
+/// let target = Content::from("[C]        \n[B]        \n[A]        \n 1   2   3 ").unwrap();
+///
+/// let plan = Plan::search(&start, &target, &CrateMover9000, 10_000).unwrap();
+/// assert_eq!(plan.render(), "move 3 from 1 to 2\nmove 3 from 2 to 3\nmove 3 from 3 to 1");
+/// assert_eq!(plan.replay(&start, &CrateMover9000).unwrap(), target);
+///
+/// let plan = Plan::search(&start, &target, &CrateMover9001, 10_000).unwrap();
+/// assert_eq!(plan.len(), 4);
+/// assert_eq!(plan.replay(&start, &CrateMover9001).unwrap(), target);
+///
+/// // there is nowhere to put crates aside
+/// let start = Content::from("[A]\n[B]\n 1 ").unwrap();
+/// let target = Content::from("[B]\n[A]\n 1 ").unwrap();
+/// assert_eq!(
+///     Plan::search(&start, &target, &CrateMover9000, 10_000).unwrap_err(),
+///     "The target cannot be reached"
+/// );
+///
+/// let other = Content::from("[A]\n 1 ").unwrap();
+/// assert_eq!(
+///     Plan::search(&start, &other, &CrateMover9000, 10_000).unwrap_err(),
+///     "Cannot turn 2 crates into 1"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    commands: Vec<Command>,
+}
+impl Plan {
+    // Best-first search over contents, visiting at most `limit` of them.
+    // Every crate above the part of its stack already matching the target
+    // has to be moved, so the plans found are short, but not always the shortest.
+    pub fn search(
+        start: &Content,
+        target: &Content,
+        model: &dyn CraneModel,
+        limit: usize,
+    ) -> Result<Self, String> {
+        if start.labels != target.labels {
+            return Err(format!(
+                "Stacks {:?} differ from {:?}",
+                start.labels, target.labels
+            ));
+        }
+        let goal = target.state();
+        let (total, expected) = (Self::count(&start.state()), Self::count(&goal));
+        if total != expected {
+            return Err(format!("Cannot turn {} crates into {}", total, expected));
         }
-        content.top()
+
+        let mut states: Vec<Visit> = Vec::new();
+        let mut index: HashMap<State, usize> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+
+        let state = start.state();
+        queue.push(Reverse((Self::estimate(&state, &goal), 0)));
+        index.insert(state.clone(), 0);
+        states.push(Visit {
+            state,
+            via: None,
+            steps: 0,
+        });
+
+        while let Some(Reverse((_, current))) = queue.pop() {
+            let state = states[current].state.clone();
+            if state == goal {
+                return Self::verified(Self::path(&states, current), start, target, model);
+            }
+            let steps = states[current].steps + 1;
+            let content = start.with_state(&state);
+            for (from, to, count) in Self::moves(&state) {
+                let command = Command {
+                    count,
+                    from: start.labels[from].clone(),
+                    to: start.labels[to].clone(),
+                };
+                let mut next = content.clone();
+                if model.apply(&command, &mut next).is_err() {
+                    continue;
+                }
+                let next = next.state();
+                match index.get(&next) {
+                    Some(&known) if states[known].steps <= steps => continue,
+                    Some(&known) => {
+                        states[known].via = Some((current, command));
+                        states[known].steps = steps;
+                    }
+                    None => {
+                        if states.len() >= limit {
+                            return Err(format!("No plan found within {} states", limit));
+                        }
+                        index.insert(next.clone(), states.len());
+                        states.push(Visit {
+                            state: next.clone(),
+                            via: Some((current, command)),
+                            steps,
+                        });
+                    }
+                }
+                queue.push(Reverse((
+                    steps + Self::estimate(&next, &goal),
+                    index[&next],
+                )));
+            }
+        }
+        Err("The target cannot be reached".into())
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
     }
+
+    // Write the plan as the commands part of the input file
+    pub fn render(&self) -> String {
+        let commands: Vec<String> = self.commands.iter().map(|c| c.render()).collect();
+        commands.join("\n")
+    }
+
+    // Run the rendered plan from the start as if it was read from the input file
+    pub fn replay(&self, start: &Content, model: &dyn CraneModel) -> Result<Content, String> {
+        let crane = Crane::parse(&format!("{}\n\n{}", start.render(), self.render()))?;
+        let mut content = crane.content.clone();
+        for step in crane.trace(model) {
+            content = step?;
+        }
+        Ok(content)
+    }
+
+    fn verified(
+        commands: Vec<Command>,
+        start: &Content,
+        target: &Content,
+        model: &dyn CraneModel,
+    ) -> Result<Self, String> {
+        let plan = Self { commands };
+        if plan.replay(start, model)? != *target {
+            return Err(format!(
+                "The plan does not reach the target:\n{}",
+                plan.render()
+            ));
+        }
+        Ok(plan)
+    }
+
+    // Commands leading to the state from the start
+    fn path(states: &[Visit], mut current: usize) -> Vec<Command> {
+        let mut commands = Vec::with_capacity(states[current].steps);
+        while let Some((parent, command)) = &states[current].via {
+            commands.push(command.clone());
+            current = *parent;
+        }
+        commands.reverse();
+        commands
+    }
+
+    // Every move of 1 or more crates between two different stacks
+    fn moves(state: &State) -> Vec<(usize, usize, usize)> {
+        let mut output = Vec::new();
+        for (from, stack) in state.iter().enumerate() {
+            for to in (0..state.len()).filter(|&to| to != from) {
+                output.extend((1..=stack.len()).map(|count| (from, to, count)));
+            }
+        }
+        output
+    }
+
+    // The number of crates lying above the matching bottom of their stacks
+    fn estimate(state: &State, goal: &State) -> usize {
+        state
+            .iter()
+            .zip(goal.iter())
+            .map(|(stack, target)| {
+                let matching = stack.iter().zip(target).take_while(|(a, b)| a == b).count();
+                stack.len() - matching
+            })
+            .sum()
+    }
+
+    fn count(state: &State) -> usize {
+        state.iter().map(|stack| stack.len()).sum()
+    }
+}
+
+// Crates of every stack from the bottom up, in the order of labels
+type State = Vec<Vec<char>>;
+
+// The state visited by the search along with the shortest known way to it
+struct Visit {
+    state: State,
+    via: Option<(usize, Command)>,
+    steps: usize,
 }
@@ -1,32 +1,7 @@
-use std::borrow::BorrowMut;
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
+use std::fmt;
 use std::fs;
-use std::str::Chars;
-
-#[derive(Debug)]
-pub struct Buffer(VecDeque<char>);
-impl Buffer {
-    pub fn from(input: &mut Chars, size: usize) -> Option<Self> {
-        let mut vd = VecDeque::new();
-        for _ in 0..size {
-            vd.push_back(input.next()?)
-        }
-        Some(Self(vd))
-    }
-
-    pub fn forward(&mut self, item: char) {
-        self.0.pop_front();
-        self.0.push_back(item);
-    }
-
-    pub fn is_uniq(&self) -> bool {
-        let mut hash: HashSet<&char> = HashSet::with_capacity(self.0.len());
-        for c in self.0.iter() {
-            hash.insert(c);
-        }
-        hash.len() == self.0.len()
-    }
-}
+use std::io::{BufReader, Bytes, Read};
 
 pub struct Stream(pub String);
 impl Stream {
@@ -59,22 +34,292 @@ impl Stream {
     /// assert_eq!(stream.start_message(), Some(26));
     /// ```
     pub fn start_packet(&self) -> Option<usize> {
-        self.find_uniq_seq(4)
+        self.find_marker(4)
     }
 
     pub fn start_message(&self) -> Option<usize> {
-        self.find_uniq_seq(14)
+        self.find_marker(14)
     }
 
-    fn find_uniq_seq(&self, size: usize) -> Option<usize> {
-        let mut chars = self.0.chars();
-        let mut buffer = Buffer::from(&mut chars, size)?;
-        for (index, c) in chars.enumerate() {
-            if buffer.is_uniq() {
-                return Some(index + size);
+    /// The number of bytes read when the last `size` of them are all different
+    /// ```
+    /// use aoc2022::Stream;
+    ///
+    /// let stream = Stream("mjqjpqmgbljsphdztnvjfqwrcgsmlb".into());
+    /// assert_eq!(stream.find_marker(1), Some(1));
+    /// assert_eq!(stream.find_marker(4), stream.start_packet());
+    /// assert_eq!(stream.find_marker(14), stream.start_message());
+    /// assert_eq!(stream.find_marker(20), None);
+    /// assert_eq!(stream.find_marker(0), None);
+    ///
+    /// let alphabet: String = ('a'..='z').chain('a'..='z').collect();
+    /// let stream = Stream(format!("aa{}", alphabet));
+    /// assert_eq!(stream.find_marker(26), Some(28));
+    /// assert_eq!(stream.find_marker(usize::MAX), None);
+    /// ```
+    pub fn find_marker(&self, size: usize) -> Option<usize> {
+        let mut window = Window::new(size);
+        for (index, &byte) in self.0.as_bytes().iter().enumerate() {
+            if window.push(byte) {
+                return Some(index + 1);
             }
-            buffer.borrow_mut().forward(c);
         }
         None
     }
+
+    /// Every number of bytes read when the last `size` of them are all different
+    /// ```
+    /// use aoc2022::Stream;
+    ///
+    /// let stream = Stream("abcabcaab".into());
+    /// assert_eq!(stream.markers(3), vec![3, 4, 5, 6, 7]);
+    /// assert!(stream.markers(4).is_empty());
+    /// assert!(stream.markers(1 << 34).is_empty());
+    /// ```
+    pub fn markers(&self, size: usize) -> Vec<usize> {
+        let mut window = Window::new(size);
+        let bytes = self.0.as_bytes();
+        (1..=bytes.len())
+            .filter(|&offset| window.push(bytes[offset - 1]))
+            .collect()
+    }
+
+    /// Payloads following every marker up to the start of the next one,
+    /// the bytes before the first marker are skipped
+    /// ```
+    /// use aoc2022::{Framing, Stream};
+    ///
+    /// let stream = Stream("aaaabcdaaaabcdbb".into());
+    /// let frames = stream.frames(&Framing::packets());
+    /// assert_eq!(frames, vec![b"aaa".as_slice(), b"bb"]);
+    ///
+    /// // the payload cannot be shorter than 4 bytes, so "abcd" is a part of it
+    /// let framing = Framing { marker: 4, min_payload: 4 };
+    /// assert_eq!(stream.frames(&framing), vec![b"aaaabcdbb".as_slice()]);
+    ///
+    /// let framing = Framing { marker: 3, min_payload: 0 };
+    /// assert_eq!(stream.frames(&framing), vec![b"daaa".as_slice(), b"dbb"]);
+    ///
+    /// let framing = Framing { marker: usize::MAX, min_payload: 0 };
+    /// assert!(stream.frames(&framing).is_empty());
+    /// ```
+    pub fn frames(&self, framing: &Framing) -> Vec<&[u8]> {
+        let bytes = self.0.as_bytes();
+        let mut window = Window::new(framing.marker);
+        let mut output = Vec::new();
+        // the start of the current payload
+        let mut start: Option<usize> = None;
+        for (index, &byte) in bytes.iter().enumerate() {
+            if start.is_some_and(|s| index < s + framing.min_payload) {
+                continue;
+            }
+            if window.push(byte) {
+                if let Some(s) = start {
+                    output.push(&bytes[s..index + 1 - framing.marker]);
+                }
+                start = Some(index + 1);
+                window.clear();
+            }
+        }
+        if let Some(s) = start {
+            output.push(&bytes[s..]);
+        }
+        output
+    }
+}
+
+/// The rule to split the stream into payloads separated by markers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framing {
+    // the number of different bytes in the marker
+    pub marker: usize,
+    // the number of bytes after the marker where the next one is not looked for
+    pub min_payload: usize,
+}
+impl Framing {
+    pub fn packets() -> Self {
+        Self {
+            marker: 4,
+            min_payload: 0,
+        }
+    }
+
+    pub fn messages() -> Self {
+        Self {
+            marker: 14,
+            min_payload: 0,
+        }
+    }
+}
+
+// No more than 256 bytes can all be different
+const MAX_MARKER: usize = 256;
+
+/// The sliding window over the last bytes of the stream,
+/// which keeps counts of the bytes to check for duplicates in constant time
+#[derive(Debug, Clone)]
+pub struct Window {
+    bytes: VecDeque<u8>,
+    size: usize,
+    counts: [usize; 256],
+    // the number of byte values present more than once
+    duplicates: usize,
+}
+impl Window {
+    pub fn new(size: usize) -> Self {
+        Self {
+            bytes: VecDeque::with_capacity(size.min(MAX_MARKER)),
+            size,
+            counts: [0; 256],
+            duplicates: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Whether the window can ever be filled with different bytes
+    pub fn can_match(&self) -> bool {
+        self.size > 0 && self.size <= MAX_MARKER
+    }
+
+    // Add the next byte, and check whether the full window has no duplicates
+    pub fn push(&mut self, byte: u8) -> bool {
+        if !self.can_match() {
+            return false;
+        }
+        if self.bytes.len() == self.size {
+            let old = self.bytes.pop_front().unwrap_or_default() as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+        self.bytes.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.duplicates += 1;
+        }
+        self.is_uniq()
+    }
+
+    pub fn is_uniq(&self) -> bool {
+        self.size > 0 && self.bytes.len() == self.size && self.duplicates == 0
+    }
+
+    // Forget all bytes seen
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.counts = [0; 256];
+        self.duplicates = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Packet,
+    Message,
+}
+
+/// The marker found after the given number of bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub marker: Marker,
+    pub offset: usize,
+}
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.marker {
+            Marker::Packet => "start-of-packet",
+            Marker::Message => "start-of-message",
+        };
+        write!(f, "{} at offset {}", name, self.offset)
+    }
+}
+
+/// Reads the datastream byte by byte, reporting the first marker of every kind,
+/// and stops reading as soon as all of them are found;
+/// markers which cannot exist (empty or longer than 256 bytes) are never reported
+/// ```
+/// use aoc2022::Decoder;
+/// use std::io::Read;
+///
+/// // the feed never ends
+/// let feed = "mjqjpqmgbljsphdztnvjfqwrcgsmlb".as_bytes().chain(std::io::repeat(b'x'));
+/// let events: Vec<String> = Decoder::new(feed).map(|e| e.unwrap().to_string()).collect();
+/// assert_eq!(events, ["start-of-packet at offset 7", "start-of-message at offset 19"]);
+///
+/// let decoder = Decoder::with_lengths("abcabcd".as_bytes(), 3, 4);
+/// let events: Vec<String> = decoder.map(|e| e.unwrap().to_string()).collect();
+/// assert_eq!(events, ["start-of-packet at offset 3", "start-of-message at offset 7"]);
+///
+/// let decoder = Decoder::with_lengths("abcd".as_bytes(), 4, 4);
+/// let events: Vec<String> = decoder.map(|e| e.unwrap().to_string()).collect();
+/// assert_eq!(events, ["start-of-packet at offset 4", "start-of-message at offset 4"]);
+///
+/// let feed = "abcd".as_bytes().chain(std::io::repeat(b'x'));
+/// let events: Vec<String> = Decoder::with_lengths(feed, 4, usize::MAX)
+///     .map(|e| e.unwrap().to_string())
+///     .collect();
+/// assert_eq!(events, ["start-of-packet at offset 4"]);
+/// ```
+pub struct Decoder<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    offset: usize,
+    // windows of markers not found yet
+    windows: Vec<(Marker, Window)>,
+    found: VecDeque<Event>,
+}
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_lengths(reader, 4, 14)
+    }
+
+    pub fn with_lengths(reader: R, packet: usize, message: usize) -> Self {
+        let mut windows = vec![
+            (Marker::Packet, Window::new(packet)),
+            (Marker::Message, Window::new(message)),
+        ];
+        windows.retain(|(_, window)| window.can_match());
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            offset: 0,
+            windows,
+            found: VecDeque::new(),
+        }
+    }
+
+    // The number of bytes read so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<Event, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.found.is_empty() && !self.windows.is_empty() {
+            let byte = match self.bytes.next()? {
+                Ok(byte) => byte,
+                Err(e) => {
+                    return Some(Err(format!("Cannot read at offset {}: {}", self.offset, e)))
+                }
+            };
+            self.offset += 1;
+            let offset = self.offset;
+            let found = &mut self.found;
+            self.windows.retain_mut(|(marker, window)| {
+                if window.push(byte) {
+                    found.push_back(Event {
+                        marker: *marker,
+                        offset,
+                    });
+                    return false;
+                }
+                true
+            });
+        }
+        self.found.pop_front().map(Ok)
+    }
 }
@@ -1,27 +1,85 @@
-use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fs;
-use std::rc::Rc;
+use std::io;
+use std::path::Path;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    File,
+    Directory,
+    // found on disk only, it has no size and is never deleted
+    Symlink,
+}
+
+/// The index of the node in the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone)]
 pub struct Node {
     pub name: String,
-    pub size: i32,
-    pub parent: Option<Tree>,
-    pub branches: Vec<Tree>,
+    pub size: u64,
+    pub kind: NodeKind,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
 }
 
-#[derive(Debug)]
-pub struct Tree(Rc<RefCell<Node>>);
+/// The file system restored from the terminal output,
+/// its nodes are stored in the list and refer to each other by ids
+/// ```
+/// use aoc2022::{NodeKind, Tree};
+///
+/// let data = [
+///     "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+///     "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+///     "$ cd e", "$ ls", "584 i", "$ cd ..", "$ cd ..",
+///     "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+/// ].join("\n");
+/// let tree = Tree::from(&data).unwrap();
+/// assert_eq!(tree.size(), 48381165);
+/// assert_eq!(tree.folder_sizes(), vec![584, 94853, 24933642, 48381165]);
+/// assert_eq!(tree.sum_of_folders_up_to(100000), 95437);
+/// assert_eq!(tree.space_to_drop(40000000), Some(24933642));
+///
+/// let e = tree.get("/a/e").unwrap();
+/// assert_eq!(tree.path(e), "/a/e");
+/// assert_eq!(tree.node(e).kind, NodeKind::Directory);
+/// assert_eq!(tree.node(tree.node(e).parent.unwrap()).name, "a");
+/// let file = tree.get("/a/e/../../b.txt").unwrap();
+/// assert_eq!(tree.node(file).kind, NodeKind::File);
+/// assert!(tree.get("/a/x").is_none());
+///
+/// let listing: Vec<(&str, u64)> = tree
+///     .children(tree.get("/a").unwrap())
+///     .iter()
+///     .map(|&id| (tree.node(id).name.as_str(), tree.node(id).size))
+///     .collect();
+/// assert_eq!(listing, vec![("e", 584), ("f", 29116), ("g", 2557), ("h.lst", 62596)]);
+///
+/// // the tree can be shared between threads
+/// fn shared<T: Send + Sync>(_: &T) {}
+/// shared(&tree);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tree {
+    // the root goes first, and every node goes after its parent
+    nodes: Vec<Node>,
+    // children of every node by their names
+    names: Vec<HashMap<String, NodeId>>,
+    // errors of entries skipped while walking the disk
+    skipped: Vec<String>,
+}
 impl Tree {
-    pub fn space_to_drop(&self, space_to_get: i32) -> Option<i32> {
-        let extra_space = self.size() - space_to_get;
+    pub fn space_to_drop(&self, space_to_get: u64) -> Option<u64> {
+        let extra_space = self.size().saturating_sub(space_to_get);
         self.folder_sizes()
             .iter()
             .find(|&&a| a >= extra_space)
             .map(|&i| i)
     }
 
-    pub fn sum_of_folders_up_to(&self, limit: i32) -> i32 {
+    pub fn sum_of_folders_up_to(&self, limit: u64) -> u64 {
         self.folder_sizes()
             .iter()
             .filter(|&&i| i <= limit)
@@ -31,98 +89,780 @@ impl Tree {
     // build a file tree from given input file
     pub fn load_from(path: &str) -> Option<Self> {
         let data = fs::read_to_string(path).ok()?;
+        Self::from(&data)
+    }
 
-        let mut lines = data.split('\n');
-        if lines.next()? != "$ cd /" {
-            return None;
-        }
+    pub fn from(data: &str) -> Option<Self> {
+        Self::parse(data).ok()
+    }
 
-        let mut root = Self::new("/", 0);
-        let mut current = root.copy();
+    /// Commands are run from the root, and repeated listings are not counted twice
+    /// ```
+    /// use aoc2022::Tree;
+    ///
+    /// let data = "$ cd a\n$ ls\n10 x\n$ cd /\n$ cd a\n$ ls\n10 x\n$ cd /\n$ ls\n5 y";
+    /// let tree = Tree::from(data).unwrap();
+    /// assert_eq!(tree.size(), 15);
+    /// assert_eq!(tree.node(tree.get("/a/x").unwrap()).size, 10);
+    ///
+    /// // paths are resolved segment by segment
+    /// let data = "$ cd /\n$ ls\ndir a\n$ cd /a\n$ ls\n5 x\n$ cd ./b/../c\n$ ls\n7 y";
+    /// let tree = Tree::from(data).unwrap();
+    /// assert_eq!(tree.node(tree.get("/a/x").unwrap()).size, 5);
+    /// assert_eq!(tree.node(tree.get("/a/c/y").unwrap()).size, 7);
+    /// assert!(tree.get("/a/b").is_some());
+    /// assert_eq!(tree.children(tree.root()).len(), 1);
+    ///
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\n10 x\n$ cd x").unwrap_err(),
+    ///     "Line 4: \"/x\" is a file"
+    /// );
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\n10 x\n$ cd /x/y").unwrap_err(),
+    ///     "Line 4: \"/x\" is a file"
+    /// );
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\n10 x\ndir x").unwrap_err(),
+    ///     "Line 4: \"/x\" is a file"
+    /// );
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\ndir x\n10 x").unwrap_err(),
+    ///     "Line 4: \"/x\" is a directory"
+    /// );
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\n10 x\n$ ls\n12 x").unwrap_err(),
+    ///     "Line 5: \"/x\" is listed with sizes 10 and 12"
+    /// );
+    /// assert_eq!(
+    ///     Tree::parse("$ cd /\n$ ls\nten x").unwrap_err(),
+    ///     "Line 3: Cannot parse \"ten x\""
+    /// );
+    /// ```
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let mut tree = Self::new();
+        let mut current = tree.root();
 
-        for line in lines {
-            if line == "$ cd .." {
-                current = current.parent()?
-            } else if line.len() > 5 && &line[0..5] == "$ cd " {
-                current = Self::insert(&mut current, &line[5..], 0);
-            } else if line.len() > 3 && &line[0..1] != "$" && &line[0..3] != "dir" {
-                let mut parts = line.split(' ');
-                let size = parts.next()?.parse().ok()?;
-                let name = parts.next()?;
-                Self::insert(&mut current, name, size);
+        for (index, line) in data.split('\n').enumerate() {
+            let error = |e: String| format!("Line {}: {}", index + 1, e);
+            // names may contain spaces, so only the line break is dropped
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.trim().is_empty() || line == "$ ls" {
+                continue;
+            } else if let Some(path) = line.strip_prefix("$ cd ") {
+                // the path is resolved the same way as by `get`, creating missing folders
+                if path.starts_with('/') {
+                    current = tree.root();
+                }
+                for name in path.split('/') {
+                    current = match name {
+                        "" | "." => current,
+                        ".." => tree.node(current).parent.unwrap_or(current),
+                        _ => tree
+                            .insert(current, name, 0, NodeKind::Directory)
+                            .map_err(error)?,
+                    };
+                }
+            } else if let Some(name) = line.strip_prefix("dir ") {
+                tree.insert(current, name, 0, NodeKind::Directory)
+                    .map_err(error)?;
+            } else {
+                let cannot_parse = || error(format!("Cannot parse {:?}", line));
+                let (size, name) = line.split_once(' ').ok_or_else(cannot_parse)?;
+                let size = size.parse().map_err(|_| cannot_parse())?;
+                tree.insert(current, name, size, NodeKind::File)
+                    .map_err(error)?;
             }
         }
-        root.finalize();
+        tree.finalize();
+
+        Ok(tree)
+    }
+
+    /// Walk the directory on disk, symbolic links are not followed but kept as empty
+    /// `Symlink` nodes. Entries which cannot be read are skipped and listed by `skipped`,
+    /// only the failure to read the directory itself is an error.
+    /// ```
+    /// use aoc2022::{NodeKind, Tree};
+    /// use std::fs;
+    ///
+    /// let dir = std::env::temp_dir().join(format!("aoc2022-walk-{}", std::process::id()));
+    /// fs::create_dir_all(dir.join("a/e")).unwrap();
+    /// fs::write(dir.join("a/e/i"), [0; 584]).unwrap();
+    /// fs::write(dir.join("a/f"), [0; 29116]).unwrap();
+    /// fs::write(dir.join("b.txt"), "hello").unwrap();
+    /// #[cfg(unix)]
+    /// std::os::unix::fs::symlink(&dir, dir.join("a/loop")).unwrap();
+    ///
+    /// let tree = Tree::walk(dir.to_str().unwrap()).unwrap();
+    /// assert_eq!(tree.node(tree.get("/a/e/i").unwrap()).size, 584);
+    /// assert_eq!(tree.sum_of_folders_up_to(1000), 584);
+    /// assert!(tree.size() >= 584 + 29116 + 5);
+    /// assert!(tree.skipped().is_empty());
+    /// #[cfg(unix)]
+    /// {
+    ///     let link = tree.node(tree.get("/a/loop").unwrap());
+    ///     assert_eq!((link.kind, link.size), (NodeKind::Symlink, 0));
+    ///     // nothing but the link is left to delete
+    ///     let protected = ["/a/e", "/a/f", "/b.txt"];
+    ///     assert_eq!(
+    ///         tree.cleanup(1, aoc2022::CleanupGoal::FewestFiles, &protected).unwrap_err(),
+    ///         "Cannot free 1 bytes, only 0 can be deleted"
+    ///     );
+    /// }
+    ///
+    /// // a folder which cannot be listed is kept empty, unless it is the root
+    /// #[cfg(unix)]
+    /// {
+    ///     use std::os::unix::fs::PermissionsExt;
+    ///     let locked = dir.join("a/e");
+    ///     fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    ///     // the superuser can read it anyway
+    ///     if fs::read_dir(&locked).is_err() {
+    ///         let tree = Tree::walk(dir.to_str().unwrap()).unwrap();
+    ///         assert_eq!(tree.node(tree.get("/a/e").unwrap()).size, 0);
+    ///         assert_eq!(tree.skipped().len(), 1);
+    ///         assert!(tree.skipped()[0].starts_with("Cannot read "));
+    ///         assert!(Tree::walk(locked.to_str().unwrap()).is_err());
+    ///     }
+    ///     fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    /// }
+    ///
+    /// fs::remove_dir_all(&dir).unwrap();
+    /// assert!(Tree::walk(dir.to_str().unwrap()).is_err());
+    /// ```
+    pub fn walk(path: &str) -> Result<Self, String> {
+        let metadata =
+            fs::symlink_metadata(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        if !metadata.is_dir() {
+            return Err(format!("{} is not a directory", path));
+        }
+        let mut tree = Self::new();
+        tree.visit(tree.root(), Path::new(path))?;
+        tree.finalize();
+
+        Ok(tree)
+    }
 
-        Some(root)
+    // errors of entries left out by the walk over the disk
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// The terminal output which builds the same tree
+    /// ```
+    /// use aoc2022::{Report, Tree};
+    ///
+    /// let data = [
+    ///     "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+    ///     "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+    ///     "$ cd e", "$ ls", "584 i", "$ cd ..", "$ cd ..",
+    ///     "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+    /// ].join("\n");
+    /// let tree = Tree::from(&data).unwrap();
+    /// assert_eq!(tree.transcript(), format!("{}\n$ cd ..", data));
+    ///
+    /// let copy = Tree::from(&tree.transcript()).unwrap();
+    /// assert_eq!(copy.listing(&Report::default()), tree.listing(&Report::default()));
+    ///
+    /// // names on disk can contain spaces
+    /// let dir = std::env::temp_dir().join(format!("aoc2022-spaces-{}", std::process::id()));
+    /// std::fs::create_dir_all(dir.join("c d")).unwrap();
+    /// std::fs::write(dir.join("c d/a b"), "hello").unwrap();
+    /// let tree = Tree::walk(dir.to_str().unwrap());
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// let transcript = tree.unwrap().transcript();
+    /// assert_eq!(transcript, "$ cd /\n$ ls\ndir c d\n$ cd c d\n$ ls\n5 a b\n$ cd ..");
+    ///
+    /// let copy = Tree::from(&transcript).unwrap();
+    /// assert_eq!(copy.node(copy.get("/c d/a b").unwrap()).size, 5);
+    /// ```
+    pub fn transcript(&self) -> String {
+        let mut lines = vec!["$ cd /".to_string()];
+        self.record(self.root(), &mut lines);
+        lines.join("\n")
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
     }
 
     // the size of the root node
-    pub fn size(&self) -> i32 {
-        Rc::clone(&self.0).borrow().size
+    pub fn size(&self) -> u64 {
+        self.node(self.root()).size
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    // files and directories right inside the node
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.node(id).children
+    }
+
+    // the absolute path to the node like "/a/e"
+    pub fn path(&self, id: NodeId) -> String {
+        let node = self.node(id);
+        match node.parent {
+            Some(parent) if parent == self.root() => format!("/{}", node.name),
+            Some(parent) => format!("{}/{}", self.path(parent), node.name),
+            None => "/".into(),
+        }
+    }
+
+    // find the node by its path from the root, "." and ".." are allowed
+    pub fn get(&self, path: &str) -> Option<NodeId> {
+        let mut current = self.root();
+        for name in path.split('/') {
+            current = match name {
+                "" | "." => current,
+                ".." => self.node(current).parent.unwrap_or(current),
+                _ => self.child(current, name)?,
+            };
+        }
+        Some(current)
     }
 
     // the plain list of folder sizes
-    pub fn folder_sizes(&self) -> Vec<i32> {
-        let mut output = vec![];
-
-        let node = self.0.borrow();
-        if !node.branches.is_empty() {
-            output.push(node.size);
-            for branch in Rc::clone(&self.0).borrow().branches.iter() {
-                for item in branch.folder_sizes() {
-                    output.push(item);
+    pub fn folder_sizes(&self) -> Vec<u64> {
+        let mut output: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter(|node| node.kind == NodeKind::Directory)
+            .map(|node| node.size)
+            .collect();
+        output.sort();
+        output
+    }
+
+    /// Files and directories with their sizes as shown in the puzzle, sorted by names
+    /// ```
+    /// use aoc2022::{Report, Tree};
+    ///
+    /// let data = [
+    ///     "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+    ///     "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+    ///     "$ cd e", "$ ls", "584 i", "$ cd ..", "$ cd ..",
+    ///     "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+    /// ].join("\n");
+    /// let tree = Tree::from(&data).unwrap();
+    ///
+    /// let report = Report { depth: Some(2), min_size: 10000, human: false };
+    /// assert_eq!(tree.listing(&report), [
+    ///     "- / (dir, size=48381165)",
+    ///     "  - a (dir, size=94853)",
+    ///     "    - f (file, size=29116)",
+    ///     "    - h.lst (file, size=62596)",
+    ///     "  - b.txt (file, size=14848514)",
+    ///     "  - c.dat (file, size=8504156)",
+    ///     "  - d (dir, size=24933642)",
+    ///     "    - d.ext (file, size=5626152)",
+    ///     "    - d.log (file, size=8033020)",
+    ///     "    - j (file, size=4060174)",
+    ///     "    - k (file, size=7214296)",
+    /// ].join("\n"));
+    ///
+    /// let report = Report { depth: Some(1), human: true, ..Report::default() };
+    /// assert_eq!(tree.listing(&report), [
+    ///     "- / (dir, size=47M)",
+    ///     "  - a (dir, size=93K)",
+    ///     "  - b.txt (file, size=15M)",
+    ///     "  - c.dat (file, size=8.2M)",
+    ///     "  - d (dir, size=24M)",
+    /// ].join("\n"));
+    /// ```
+    pub fn listing(&self, report: &Report) -> String {
+        let mut lines = vec![];
+        self.list(self.root(), 0, report, &mut lines);
+        lines.join("\n")
+    }
+
+    /// Sizes of directories like `du` shows them, the largest first
+    /// ```
+    /// use aoc2022::{Report, Tree};
+    ///
+    /// let data = [
+    ///     "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+    ///     "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+    ///     "$ cd e", "$ ls", "584 i", "$ cd ..", "$ cd ..",
+    ///     "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+    /// ].join("\n");
+    /// let tree = Tree::from(&data).unwrap();
+    ///
+    /// assert_eq!(
+    ///     tree.usage(&Report::default()),
+    ///     "48381165\t/\n24933642\t/d\n94853\t/a\n584\t/a/e"
+    /// );
+    ///
+    /// let report = Report { depth: Some(1), min_size: 100000, human: true };
+    /// assert_eq!(tree.usage(&report), "47M\t/\n24M\t/d");
+    /// ```
+    pub fn usage(&self, report: &Report) -> String {
+        let mut folders: Vec<(u64, String)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (NodeId(index), node))
+            .filter(|(_, node)| node.kind == NodeKind::Directory && node.size >= report.min_size)
+            .filter(|&(id, _)| report.depth.is_none_or(|depth| self.depth(id) <= depth))
+            .map(|(id, node)| (node.size, self.path(id)))
+            .collect();
+        folders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        folders
+            .iter()
+            .map(|(size, path)| format!("{}\t{}", report.size(*size), path))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // the number of steps from the root to the node
+    fn depth(&self, id: NodeId) -> usize {
+        match self.node(id).parent {
+            Some(parent) => self.depth(parent) + 1,
+            None => 0,
+        }
+    }
+
+    fn list(&self, id: NodeId, depth: usize, report: &Report, lines: &mut Vec<String>) {
+        let node = self.node(id);
+        let (kind, name) = match node.kind {
+            NodeKind::Directory if id == self.root() => ("dir", "/"),
+            NodeKind::Directory => ("dir", node.name.as_str()),
+            NodeKind::File => ("file", node.name.as_str()),
+            NodeKind::Symlink => ("link", node.name.as_str()),
+        };
+        lines.push(format!(
+            "{}- {} ({}, size={})",
+            "  ".repeat(depth),
+            name,
+            kind,
+            report.size(node.size)
+        ));
+        if report.depth.is_some_and(|limit| depth >= limit) {
+            return;
+        }
+
+        let mut children: Vec<NodeId> = self
+            .children(id)
+            .iter()
+            .copied()
+            .filter(|&child| self.node(child).size >= report.min_size)
+            .collect();
+        children.sort_by(|&a, &b| self.node(a).name.cmp(&self.node(b).name));
+        for child in children {
+            self.list(child, depth + 1, report, lines);
+        }
+    }
+
+    /// Choose files to delete to free the space needed, keeping the protected paths intact.
+    /// The search for the fewest bytes is limited in steps, so on large trees it can stop
+    /// with the best selection found so far, which is marked as not optimal.
+    /// ```
+    /// use aoc2022::{CleanupGoal, Tree};
+    ///
+    /// let data = [
+    ///     "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+    ///     "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+    ///     "$ cd e", "$ ls", "584 i", "$ cd ..", "$ cd ..",
+    ///     "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+    /// ].join("\n");
+    /// let tree = Tree::from(&data).unwrap();
+    ///
+    /// // deleting the whole "/d" would free 24933642 bytes
+    /// let cleanup = tree.cleanup(8381165, CleanupGoal::FewestBytes, &[]).unwrap();
+    /// assert_eq!(cleanup.freed, 8504156);
+    /// assert_eq!(cleanup.report(), "8504156\t/c.dat\n8504156\ttotal");
+    ///
+    /// let cleanup = tree.cleanup(8381165, CleanupGoal::FewestBytes, &["/c.dat"]).unwrap();
+    /// assert_eq!(cleanup.freed, 5626152 + 4060174);
+    ///
+    /// let cleanup = tree.cleanup(20000000, CleanupGoal::FewestFiles, &["/d"]).unwrap();
+    /// assert_eq!(cleanup.files.len(), 2);
+    /// assert_eq!(cleanup.freed, 14848514 + 8504156);
+    ///
+    /// assert_eq!(
+    ///     tree.cleanup(40000000, CleanupGoal::FewestFiles, &["/b.txt"]).unwrap_err(),
+    ///     "Cannot free 40000000 bytes, only 33532651 can be deleted"
+    /// );
+    /// assert_eq!(
+    ///     tree.cleanup(1, CleanupGoal::FewestFiles, &["/x"]).unwrap_err(),
+    ///     "Unknown path \"/x\""
+    /// );
+    ///
+    /// let tree = Tree::load_from("data/7.in").unwrap();
+    /// let need = tree.size() - 40000000;
+    /// let cleanup = tree.cleanup(need, CleanupGoal::FewestBytes, &[]).unwrap();
+    /// assert_eq!(cleanup.freed, need);
+    /// assert!(cleanup.freed < tree.space_to_drop(40000000).unwrap());
+    /// assert!(cleanup.optimal);
+    ///
+    /// // even sizes never sum up to the odd target exactly
+    /// let mut data = vec!["$ cd /".to_string(), "$ ls".to_string()];
+    /// data.extend((1..=60).map(|i| format!("{} f{}", 2 * (1000 + i * i), i)));
+    /// let tree = Tree::from(&data.join("\n")).unwrap();
+    /// let cleanup = tree.cleanup(tree.size() / 2 + 1, CleanupGoal::FewestBytes, &[]).unwrap();
+    /// assert!(cleanup.freed > tree.size() / 2);
+    /// assert!(!cleanup.optimal);
+    /// ```
+    pub fn cleanup(
+        &self,
+        space_to_free: u64,
+        goal: CleanupGoal,
+        protected: &[&str],
+    ) -> Result<Cleanup, String> {
+        // nodes are stored after their parents, so protection is inherited in one pass
+        let mut kept = vec![false; self.nodes.len()];
+        for path in protected {
+            let id = self
+                .get(path)
+                .ok_or_else(|| format!("Unknown path {:?}", path))?;
+            kept[id.0] = true;
+        }
+        for index in 1..self.nodes.len() {
+            if let Some(parent) = self.nodes[index].parent {
+                kept[index] |= kept[parent.0];
+            }
+        }
+
+        let mut files: Vec<(u64, NodeId)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|&(index, node)| node.kind == NodeKind::File && !kept[index])
+            .map(|(index, node)| (node.size, NodeId(index)))
+            .collect();
+        files.sort_by_key(|&(size, _)| Reverse(size));
+
+        let available: u64 = files.iter().map(|&(size, _)| size).sum();
+        if available < space_to_free {
+            return Err(format!(
+                "Cannot free {} bytes, only {} can be deleted",
+                space_to_free, available
+            ));
+        }
+
+        let mut optimal = true;
+        let chosen: Vec<(u64, NodeId)> = match goal {
+            CleanupGoal::FewestFiles => {
+                let mut freed = 0;
+                files
+                    .into_iter()
+                    .take_while(|&(size, _)| {
+                        let take = freed < space_to_free;
+                        freed += size;
+                        take
+                    })
+                    .collect()
+            }
+            CleanupGoal::FewestBytes => {
+                let mut search = Selection::new(&files, space_to_free);
+                search.branch(0, 0);
+                optimal = search.steps > 0 || search.best_size == space_to_free;
+                search.best.iter().map(|&index| files[index]).collect()
+            }
+        };
+
+        Ok(Cleanup {
+            optimal,
+            freed: chosen.iter().map(|&(size, _)| size).sum(),
+            files: chosen
+                .into_iter()
+                .map(|(size, id)| (self.path(id), size))
+                .collect(),
+        })
+    }
+
+    // list the directory, entries which cannot be read are noted and skipped
+    fn visit(&mut self, id: NodeId, path: &Path) -> Result<(), String> {
+        let error = |path: &Path, e: io::Error| format!("Cannot read {}: {}", path.display(), e);
+        let mut entries = vec![];
+        for entry in fs::read_dir(path).map_err(|e| error(path, e))? {
+            match entry {
+                Ok(entry) => entries.push(entry),
+                Err(e) => self.skipped.push(error(path, e)),
+            }
+        }
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // the metadata of the link itself, not of its target
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    self.skipped.push(error(&entry.path(), e));
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                let child = self.push(id, &name, 0, NodeKind::Directory);
+                if let Err(e) = self.visit(child, &entry.path()) {
+                    self.skipped.push(e);
                 }
+            } else if metadata.is_symlink() {
+                self.push(id, &name, 0, NodeKind::Symlink);
+            } else {
+                self.push(id, &name, metadata.len(), NodeKind::File);
             }
         }
+        Ok(())
+    }
 
-        if let None = node.parent {
-            output.sort_by(|a, b| a.cmp(&b));
+    // list the directory and go into its subdirectories
+    fn record(&self, id: NodeId, lines: &mut Vec<String>) {
+        lines.push("$ ls".into());
+        for &child in self.children(id) {
+            let node = self.node(child);
+            lines.push(match node.kind {
+                NodeKind::Directory => format!("dir {}", node.name),
+                // the puzzle has no links, so they are shown as empty files
+                NodeKind::File | NodeKind::Symlink => format!("{} {}", node.size, node.name),
+            });
+        }
+        for &child in self.children(id) {
+            if self.node(child).kind == NodeKind::Directory {
+                lines.push(format!("$ cd {}", self.node(child).name));
+                self.record(child, lines);
+                lines.push("$ cd ..".into());
+            }
         }
-        output
     }
 
-    fn new(name: &str, size: i32) -> Self {
-        let name = name.into();
-        Self(Rc::new(RefCell::new(Node {
-            name,
-            size,
+    fn new() -> Self {
+        let root = Node {
+            name: String::new(),
+            size: 0,
+            kind: NodeKind::Directory,
             parent: None,
-            branches: vec![],
-        })))
+            children: vec![],
+        };
+        Self {
+            nodes: vec![root],
+            names: vec![HashMap::new()],
+            skipped: vec![],
+        }
     }
 
-    fn copy(&self) -> Self {
-        Self(Rc::clone(&self.0))
+    // add the node unless the same one exists already, a different one is an error
+    fn insert(
+        &mut self,
+        parent: NodeId,
+        name: &str,
+        size: u64,
+        kind: NodeKind,
+    ) -> Result<NodeId, String> {
+        let Some(child) = self.child(parent, name) else {
+            return Ok(self.push(parent, name, size, kind));
+        };
+        let node = self.node(child);
+        match (node.kind, kind) {
+            (NodeKind::File, NodeKind::Directory) => {
+                Err(format!("{:?} is a file", self.path(child)))
+            }
+            (NodeKind::Directory, NodeKind::File) => {
+                Err(format!("{:?} is a directory", self.path(child)))
+            }
+            (NodeKind::File, NodeKind::File) if node.size != size => Err(format!(
+                "{:?} is listed with sizes {} and {}",
+                self.path(child),
+                node.size,
+                size
+            )),
+            _ => Ok(child),
+        }
     }
 
-    fn insert(&self, name: &str, size: i32) -> Self {
-        let child = Self::new(name, size);
-        self.0.borrow_mut().branches.push(child.copy());
-        {
-            let mut mut_child = child.0.borrow_mut();
-            mut_child.parent = Some(self.copy());
-        }
+    // add the node whose name is known to be new in the parent
+    fn push(&mut self, parent: NodeId, name: &str, size: u64, kind: NodeKind) -> NodeId {
+        let child = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            name: name.into(),
+            size,
+            kind,
+            parent: Some(parent),
+            children: vec![],
+        });
+        self.nodes[parent.0].children.push(child);
+        self.names.push(HashMap::new());
+        self.names[parent.0].insert(name.into(), child);
         child
     }
 
-    fn parent(&self) -> Option<Self> {
-        let current_clone = Rc::clone(&self.0);
-        let borrow = current_clone.borrow();
-        Some(borrow.parent.as_ref()?.copy())
+    fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        self.names[id.0].get(name).copied()
     }
 
-    fn finalize(&mut self) -> i32 {
-        let clone = Rc::clone(&self.0);
-        let mut node = clone.borrow_mut();
-        if !node.branches.is_empty() {
-            node.size = {
-                node.branches
-                    .iter_mut()
-                    .map(|b| b.finalize())
-                    .fold(0, |a, i| a + i)
-            };
+    // sum up sizes of directories from the deepest nodes to the root
+    fn finalize(&mut self) {
+        for index in (0..self.nodes.len()).rev() {
+            if let Some(parent) = self.nodes[index].parent {
+                self.nodes[parent.0].size += self.nodes[index].size;
+            }
+        }
+    }
+}
+
+/// Options of the tree listing and the disk usage summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Report {
+    // the deepest level to show, the root is at level 0
+    pub depth: Option<usize>,
+    // smaller nodes are skipped along with their contents
+    pub min_size: u64,
+    // show sizes in K, M, G, T like `du -h` does
+    pub human: bool,
+}
+impl Report {
+    fn size(&self, size: u64) -> String {
+        if self.human {
+            human_size(size)
+        } else {
+            size.to_string()
+        }
+    }
+}
+
+/// Size in powers of 1024 rounded up like `du -h` does,
+/// with one decimal digit for values below 10
+/// ```
+/// use aoc2022::human_size;
+///
+/// assert_eq!(human_size(584), "584");
+/// assert_eq!(human_size(1536), "1.5K");
+/// assert_eq!(human_size(94853), "93K");
+/// assert_eq!(human_size(48381165), "47M");
+/// assert_eq!(human_size(10199), "10K");
+/// assert_eq!(human_size(1048064), "1.0M");
+/// assert_eq!(human_size(5 << 40), "5.0T");
+/// assert_eq!(human_size(u64::MAX), "16E");
+/// ```
+pub fn human_size(size: u64) -> String {
+    if size < 1024 {
+        return size.to_string();
+    }
+    let units = ["K", "M", "G", "T", "P", "E"];
+    for (index, unit) in units.iter().enumerate() {
+        let scale = 1u128 << (10 * (index + 1));
+        let tenths = (size as u128 * 10).div_ceil(scale);
+        if tenths < 100 {
+            return format!("{}.{}{}", tenths / 10, tenths % 10, unit);
+        }
+        let whole = (size as u128).div_ceil(scale);
+        if whole < 1024 || index + 1 == units.len() {
+            return format!("{}{}", whole, unit);
+        }
+    }
+    unreachable!()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupGoal {
+    // delete as few bytes as possible
+    FewestBytes,
+    // delete as few files as possible
+    FewestFiles,
+}
+
+/// Files to delete with their sizes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cleanup {
+    pub files: Vec<(String, u64)>,
+    pub freed: u64,
+    // false when the search ran out of steps before proving the choice is the best
+    pub optimal: bool,
+}
+impl Cleanup {
+    // the list of files like `du` shows them with the total at the end
+    pub fn report(&self) -> String {
+        let mut lines: Vec<String> = self
+            .files
+            .iter()
+            .map(|(path, size)| format!("{}\t{}", size, path))
+            .collect();
+        lines.push(format!("{}\ttotal", self.freed));
+        lines.join("\n")
+    }
+}
+
+// The number of branches the search may try before giving up
+const SELECTION_STEPS: usize = 1_000_000;
+
+// Branch and bound search for the subset of files with the smallest total
+// size reaching the target, files are sorted from the largest one.
+// It starts from the greedy choice and improves it until it runs out of steps.
+struct Selection<'a> {
+    files: &'a [(u64, NodeId)],
+    steps: usize,
+    // the total size of files starting from the index
+    rest: Vec<u64>,
+    target: u64,
+    chosen: Vec<usize>,
+    best: Vec<usize>,
+    best_size: u64,
+}
+impl<'a> Selection<'a> {
+    fn new(files: &'a [(u64, NodeId)], target: u64) -> Self {
+        let mut rest = vec![0; files.len() + 1];
+        for index in (0..files.len()).rev() {
+            rest[index] = rest[index + 1] + files[index].0;
+        }
+
+        // take files while they fit below the target, and finish with the smallest one
+        // crossing it
+        let (mut best, mut best_size) = ((0..files.len()).collect(), rest[0]);
+        let (mut taken, mut size) = (vec![], 0);
+        for (index, &(file, _)) in files.iter().enumerate() {
+            if size + file < target {
+                taken.push(index);
+                size += file;
+            } else if size + file < best_size {
+                best = taken.iter().copied().chain([index]).collect();
+                best_size = size + file;
+            }
+        }
+
+        Self {
+            files,
+            steps: SELECTION_STEPS,
+            best,
+            best_size,
+            rest,
+            target,
+            chosen: vec![],
+        }
+    }
+
+    fn branch(&mut self, index: usize, size: u64) {
+        if self.steps == 0 {
+            return;
+        }
+        self.steps -= 1;
+
+        if size >= self.target {
+            if size < self.best_size {
+                self.best_size = size;
+                self.best = self.chosen.clone();
+            }
+            return;
+        }
+        if index == self.files.len() || size + self.rest[index] < self.target {
+            return;
+        }
+
+        let file = self.files[index].0;
+        if size + file < self.best_size {
+            self.chosen.push(index);
+            self.branch(index + 1, size + file);
+            self.chosen.pop();
+        }
+        if self.best_size == self.target {
+            return;
+        }
+        // skipping the file makes no sense to take another one of the same size
+        let mut next = index + 1;
+        while next < self.files.len() && self.files[next].0 == file {
+            next += 1;
         }
-        node.size
+        self.branch(next, size);
     }
 }